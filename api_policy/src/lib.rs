@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::{Client, Request, Response, StatusCode};
+use serde_derive::Deserialize;
+use tokio::time::sleep;
+use tracing::debug;
+
+mod limiter;
+pub use limiter::{RateLimit, TokenBucket, MIN_REQUESTS_PER_SEC};
+
+/// Etherscan free tier allows 5 requests per second.
+pub const ETHERSCAN_HOST: &str = "api.etherscan.io";
+
+/// Retry settings for transient failures: timeouts, connection errors, http 429 and 5xx responses.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Retries after the first attempt, `0` disables retrying.
+    pub max_retries: u32,
+    /// Backoff of the first retry, doubled on every further retry.
+    pub base_delay_ms: u64,
+    /// Upper bound of the backoff.
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay_ms: 250,
+            max_delay_ms: 5000,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Full jitter backoff before retry number `attempt` (starting at 0): random delay up to `base * 2^attempt`.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self.base_delay_ms
+            .saturating_mul(1u64.checked_shl(attempt).unwrap_or(u64::MAX))
+            .min(self.max_delay_ms);
+        Duration::from_millis(rand::thread_rng().gen_range(0..=ceiling))
+    }
+}
+
+/// Retry and rate limit settings, `[request_policy]` table in the bot config.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PolicyConfig {
+    pub retry: RetryConfig,
+    /// Token bucket limits keyed by upstream host name, eg.: `api.etherscan.io`.
+    pub rate_limits: HashMap<String, RateLimit>,
+}
+
+impl Default for PolicyConfig {
+    fn default() -> Self {
+        let mut rate_limits = HashMap::new();
+        rate_limits.insert(ETHERSCAN_HOST.to_string(), RateLimit { requests_per_sec: 5.0, burst: 5 });
+        Self {
+            retry: RetryConfig::default(),
+            rate_limits,
+        }
+    }
+}
+
+static GLOBAL_POLICY: OnceLock<Arc<RequestPolicy>> = OnceLock::new();
+
+/// Retry policy with one rate limiter per upstream host, shared by every api crate.
+#[derive(Debug)]
+pub struct RequestPolicy {
+    retry: RetryConfig,
+    rate_limits: HashMap<String, RateLimit>,
+    limiters: Mutex<HashMap<String, Arc<TokenBucket>>>,
+}
+
+impl Default for RequestPolicy {
+    fn default() -> Self {
+        Self::new(PolicyConfig::default())
+    }
+}
+
+impl RequestPolicy {
+    pub fn new(config: PolicyConfig) -> Self {
+        Self {
+            retry: config.retry,
+            rate_limits: config.rate_limits,
+            limiters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the process wide policy, returns false if it was already set or used.
+    pub fn init_global(config: PolicyConfig) -> bool {
+        GLOBAL_POLICY.set(Arc::new(Self::new(config))).is_ok()
+    }
+
+    /// Process wide policy, the default settings unless [`RequestPolicy::init_global`] was called first.
+    pub fn global() -> Arc<RequestPolicy> {
+        Arc::clone(GLOBAL_POLICY.get_or_init(|| Arc::new(Self::default())))
+    }
+
+    pub fn retry(&self) -> RetryConfig {
+        self.retry
+    }
+
+    /// Executes the request under the host rate limit, retrying transient failures.
+    /// Requests with a streaming body can't be cloned, those are sent once.
+    pub async fn execute(&self, client: &Client, mut request: Request) -> reqwest::Result<Response> {
+        let limiter = request.url().host_str().and_then(|host| self.limiter(host));
+        let mut attempt = 0;
+        loop {
+            let next_request = if attempt < self.retry.max_retries { request.try_clone() } else { None };
+            let url = request.url().clone();
+            let result = self.send_once(client, request, limiter.as_deref()).await;
+            let next_request = match next_request {
+                Some(next_request) => next_request,
+                None => return result,
+            };
+            let delay = match &result {
+                Ok(response) if is_transient_status(response.status()) => {
+                    retry_after(response)
+                        .map(|delay| delay.min(Duration::from_millis(self.retry.max_delay_ms)))
+                        .unwrap_or_else(|| self.retry.backoff(attempt))
+                },
+                Err(e) if e.is_timeout() || e.is_connect() || e.is_request() => self.retry.backoff(attempt),
+                _ => return result,
+            };
+            debug!("request to {} failed, retry {} in {:?}", url, attempt + 1, delay);
+            sleep(delay).await;
+            request = next_request;
+            attempt += 1;
+        }
+    }
+
+    async fn send_once(&self, client: &Client, request: Request, limiter: Option<&TokenBucket>) -> reqwest::Result<Response> {
+        if let Some(limiter) = limiter {
+            limiter.acquire().await;
+        }
+        client.execute(request).await
+    }
+
+    fn limiter(&self, host: &str) -> Option<Arc<TokenBucket>> {
+        let limit = self.rate_limits.get(host)?;
+        let mut limiters = self.limiters.lock().expect("rate limiter lock poisoned");
+        let limiter = limiters.entry(host.to_string()).or_insert_with(|| Arc::new(TokenBucket::new(*limit)));
+        Some(Arc::clone(limiter))
+    }
+}
+
+fn is_transient_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+// `Retry-After` header in seconds, the http date form is not used by the apis we call.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let seconds = response.headers().get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim().parse().ok()?;
+    Some(Duration::from_secs(seconds))
+}
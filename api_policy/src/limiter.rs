@@ -0,0 +1,82 @@
+use std::time::{Duration, Instant};
+
+use serde::de::{Deserialize, Deserializer, Error};
+use serde_derive::Deserialize;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+/// Slowest rate a bucket refills at, one request per 1000 seconds.
+pub const MIN_REQUESTS_PER_SEC: f64 = 0.001;
+
+/// Token bucket settings for one upstream host.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct RateLimit {
+    /// Sustained request rate, a positive number of at least [`MIN_REQUESTS_PER_SEC`].
+    #[serde(deserialize_with = "requests_per_sec")]
+    pub requests_per_sec: f64,
+    /// Requests allowed in a burst above the sustained rate, at least 1.
+    #[serde(default = "default_burst")]
+    pub burst: u32,
+}
+
+fn default_burst() -> u32 {
+    1
+}
+
+// A zero, negative or infinite rate in the config would make the bucket wait forever.
+fn requests_per_sec<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let rate = f64::deserialize(deserializer)?;
+    if !rate.is_finite() || rate < MIN_REQUESTS_PER_SEC {
+        return Err(D::Error::custom(format!("requests_per_sec must be at least {}, got {}", MIN_REQUESTS_PER_SEC, rate)))
+    }
+    Ok(rate)
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Token bucket rate limiter, waits until a request is allowed instead of failing it.
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    state: Mutex<BucketState>,
+}
+
+impl TokenBucket {
+    /// Rates below [`MIN_REQUESTS_PER_SEC`], or not a number, refill at the minimum rate.
+    pub fn new(limit: RateLimit) -> Self {
+        let capacity = f64::from(limit.burst.max(1));
+        let rate = if limit.requests_per_sec.is_nan() { MIN_REQUESTS_PER_SEC } else { limit.requests_per_sec.max(MIN_REQUESTS_PER_SEC) };
+        Self {
+            rate,
+            capacity,
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes one token, sleeping until one is available.
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().await;
+                let now = Instant::now();
+                let refill = now.duration_since(state.refilled_at).as_secs_f64() * self.rate;
+                state.tokens = (state.tokens + refill).min(self.capacity);
+                state.refilled_at = now;
+                if state.tokens >= 1.0 {
+                    state.tokens -= 1.0;
+                    return
+                }
+                Duration::from_secs_f64((1.0 - state.tokens) / self.rate)
+            };
+            sleep(wait).await;
+        }
+    }
+}
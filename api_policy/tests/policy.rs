@@ -0,0 +1,109 @@
+use std::time::{Duration, Instant};
+
+use api_policy::{PolicyConfig, RateLimit, RequestPolicy, RetryConfig, TokenBucket};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+use wiremock::matchers::{method, path};
+
+fn fast_retry_policy(max_retries: u32) -> RequestPolicy {
+    RequestPolicy::new(PolicyConfig {
+        retry: RetryConfig { max_retries, base_delay_ms: 10, max_delay_ms: 50 },
+        ..PolicyConfig::default()
+    })
+}
+
+async fn get_status(policy: &RequestPolicy, url: &str) -> u16 {
+    let client = reqwest::Client::new();
+    let request = client.get(url).build().unwrap();
+    policy.execute(&client, request).await.unwrap().status().as_u16()
+}
+
+#[tokio::test]
+async fn retries_server_errors_until_success() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/flaky"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .expect(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/flaky"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    assert_eq!(get_status(&fast_retry_policy(3), &format!("{}/flaky", server.uri())).await, 200);
+}
+
+#[tokio::test]
+async fn gives_up_after_max_retries() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/limited"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    assert_eq!(get_status(&fast_retry_policy(2), &format!("{}/limited", server.uri())).await, 429);
+}
+
+#[tokio::test]
+async fn client_errors_are_not_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/missing"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    assert_eq!(get_status(&fast_retry_policy(3), &format!("{}/missing", server.uri())).await, 404);
+}
+
+#[tokio::test]
+async fn host_rate_limit_spaces_requests() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    let mut config = PolicyConfig::default();
+    config.rate_limits.insert("127.0.0.1".into(), RateLimit { requests_per_sec: 20.0, burst: 1 });
+    let policy = RequestPolicy::new(config);
+
+    let started = Instant::now();
+    for _ in 0..3 {
+        assert_eq!(get_status(&policy, &server.uri()).await, 200);
+    }
+    assert!(started.elapsed() >= Duration::from_millis(90));
+}
+
+#[test]
+fn backoff_stays_under_the_cap() {
+    let retry = RetryConfig { max_retries: 10, base_delay_ms: 100, max_delay_ms: 1000 };
+    for attempt in 0..70 {
+        assert!(retry.backoff(attempt) <= Duration::from_millis(1000));
+    }
+    assert!(retry.backoff(0) <= Duration::from_millis(100));
+}
+
+#[test]
+fn non_positive_rates_are_rejected() {
+    for rate in &["0", "-1.0", "inf", "nan"] {
+        let config = format!("[rate_limits.\"api.etherscan.io\"]\nrequests_per_sec = {}", rate);
+        let error = toml::from_str::<PolicyConfig>(&config).unwrap_err();
+        assert!(error.to_string().contains("requests_per_sec"), "{}", error);
+    }
+    let config: PolicyConfig = toml::from_str("[rate_limits.\"api.etherscan.io\"]\nrequests_per_sec = 0.5").unwrap();
+    assert_eq!(config.rate_limits["api.etherscan.io"].requests_per_sec, 0.5);
+}
+
+#[tokio::test]
+async fn zero_rate_bucket_waits_without_panicking() {
+    let bucket = TokenBucket::new(RateLimit { requests_per_sec: 0.0, burst: 1 });
+    bucket.acquire().await;
+    assert!(tokio::time::timeout(Duration::from_millis(20), bucket.acquire()).await.is_err());
+}
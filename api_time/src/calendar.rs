@@ -0,0 +1,54 @@
+//! Starts of calendar days, weeks, months and years in a timezone, following its daylight saving changes.
+//! `None` for timestamps past the dates the calendar can represent.
+
+use std::convert::TryFrom;
+
+use chrono::{Datelike, Duration, LocalResult, NaiveDate, TimeZone, Weekday};
+pub use chrono_tz::Tz;
+
+/// Unix timestamp of the midnight starting the day `timestamp` falls in.
+pub fn start_of_day(timestamp: u64, tz: Tz) -> Option<u64> {
+    let date = local_date(timestamp, tz)?;
+    start_of(date, tz)
+}
+
+/// Unix timestamp of the Monday midnight starting the week `timestamp` falls in.
+pub fn start_of_week(timestamp: u64, tz: Tz) -> Option<u64> {
+    let date = local_date(timestamp, tz)?;
+    start_of(date.week(Weekday::Mon).first_day(), tz)
+}
+
+/// Unix timestamp of the midnight starting the month `timestamp` falls in.
+pub fn start_of_month(timestamp: u64, tz: Tz) -> Option<u64> {
+    let date = local_date(timestamp, tz)?;
+    start_of(date.with_day(1)?, tz)
+}
+
+/// Unix timestamp of the January 1st midnight starting the year `timestamp` falls in.
+pub fn start_of_year(timestamp: u64, tz: Tz) -> Option<u64> {
+    let date = local_date(timestamp, tz)?;
+    start_of(date.with_ordinal(1)?, tz)
+}
+
+// `None` past the last date the calendar can represent.
+fn local_date(timestamp: u64, tz: Tz) -> Option<NaiveDate> {
+    let seconds = i64::try_from(timestamp).ok()?;
+    match tz.timestamp_opt(seconds, 0) {
+        LocalResult::Single(time) | LocalResult::Ambiguous(time, _) => Some(time.date_naive()),
+        LocalResult::None => None,
+    }
+}
+
+// Where a daylight saving change skips midnight the day starts at the first local time after the gap.
+fn start_of(date: NaiveDate, tz: Tz) -> Option<u64> {
+    let midnight = date.and_hms_opt(0, 0, 0)?;
+    let next_midnight = midnight.checked_add_signed(Duration::days(1))?;
+    let mut local = midnight;
+    while local < next_midnight {
+        if let Some(start) = tz.from_local_datetime(&local).earliest() {
+            return u64::try_from(start.timestamp()).ok()
+        }
+        local += Duration::minutes(15);
+    }
+    u64::try_from(midnight.and_utc().timestamp()).ok()
+}
@@ -0,0 +1,49 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Source of the current time, the api clients take one so time dependent calculations can run at a fixed time in tests.
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Current unix timestamp in seconds.
+    fn now(&self) -> u64;
+}
+
+/// Clock of the system time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |epoch| epoch.as_secs())
+    }
+}
+
+/// Clock standing at a set time until it's moved, clones share the time.
+#[derive(Debug, Clone, Default)]
+pub struct FixedClock(Arc<AtomicU64>);
+
+impl FixedClock {
+    pub fn new(timestamp: u64) -> Self {
+        FixedClock(Arc::new(AtomicU64::new(timestamp)))
+    }
+
+    pub fn set(&self, timestamp: u64) {
+        self.0.store(timestamp, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, seconds: u64) {
+        self.0.fetch_add(seconds, Ordering::SeqCst);
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// The default clock of the api clients.
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
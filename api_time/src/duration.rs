@@ -0,0 +1,34 @@
+//! Lengths of time in seconds, `None` where the count overflows instead of wrapping around.
+//! A month is 4 weeks and a year is 52 weeks as the Invictus api counts them, see [`crate::calendar`] for calendar boundaries.
+
+pub const MINUTE: u64 = 60;
+pub const HOUR: u64 = 60 * MINUTE;
+pub const DAY: u64 = 24 * HOUR;
+pub const WEEK: u64 = 7 * DAY;
+pub const MONTH: u64 = 4 * WEEK;
+pub const YEAR: u64 = 52 * WEEK;
+
+pub fn hours(count: u64) -> Option<u64> {
+    count.checked_mul(HOUR)
+}
+
+pub fn days(count: u64) -> Option<u64> {
+    count.checked_mul(DAY)
+}
+
+pub fn weeks(count: u64) -> Option<u64> {
+    count.checked_mul(WEEK)
+}
+
+pub fn months(count: u64) -> Option<u64> {
+    count.checked_mul(MONTH)
+}
+
+pub fn years(count: u64) -> Option<u64> {
+    count.checked_mul(YEAR)
+}
+
+/// Unix timestamp `seconds` before `timestamp`, `None` before the unix epoch.
+pub fn before(timestamp: u64, seconds: u64) -> Option<u64> {
+    timestamp.checked_sub(seconds)
+}
@@ -0,0 +1,142 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use serde_derive::Deserialize;
+use thiserror::Error;
+
+mod clock;
+pub use clock::{system_clock, Clock, FixedClock, SystemClock};
+pub mod duration;
+use duration::{HOUR, WEEK};
+pub mod calendar;
+pub use calendar::Tz;
+
+/// Ranges listed by the perf commands when no range is given.
+pub const DEFAULT_RANGES: [TimeRange; 6] = [
+    TimeRange::Hours(1),
+    TimeRange::Hours(12),
+    TimeRange::Days(1),
+    TimeRange::Weeks(1),
+    TimeRange::Months(1),
+    TimeRange::Years(1),
+];
+
+/// Longest range the Invictus api movement endpoint reads in hours, longer ranges are read in whole weeks.
+pub const INVICTUS_MAX_HOURS: u32 = 48;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TimeRangeError {
+    #[error("invalid time range `{0}`, expected eg.: 1h, 12h, 1d, 1w, 1m, 1y or ytd")]
+    Invalid(String),
+    #[error("time range `{range}` is out of limits, use 1{unit} to {max}{unit}")]
+    OutOfLimits { range: String, max: u32, unit: char },
+}
+
+/// Lookback period of a performance query, parsed from `1h`, `12h`, `1d`, `1w`, `1m`, `1y` or `ytd`.
+/// A month is 4 weeks and a year is 52 weeks, as the Invictus api counts them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub enum TimeRange {
+    Hours(u32),
+    Days(u32),
+    Weeks(u32),
+    Months(u32),
+    Years(u32),
+    /// Since the start of the current year in UTC.
+    YearToDate,
+}
+
+impl TimeRange {
+    /// Length of the range in seconds ending at the `now` unix timestamp, only `ytd` depends on it.
+    /// `None` when the year start of `now` is past the dates the calendar can represent.
+    pub fn seconds_at(&self, now: u64) -> Option<u64> {
+        match *self {
+            TimeRange::Hours(count) => duration::hours(u64::from(count)),
+            TimeRange::Days(count) => duration::days(u64::from(count)),
+            TimeRange::Weeks(count) => duration::weeks(u64::from(count)),
+            TimeRange::Months(count) => duration::months(u64::from(count)),
+            TimeRange::Years(count) => duration::years(u64::from(count)),
+            TimeRange::YearToDate => now.checked_sub(calendar::start_of_year(now, Tz::UTC)?),
+        }
+    }
+
+    /// Unix timestamp the range ending at `now` starts at, `None` before the unix epoch.
+    pub fn start_at(&self, now: u64) -> Option<u64> {
+        duration::before(now, self.seconds_at(now)?)
+    }
+
+    /// Value of the `range` query parameter of the Invictus api movement endpoint for the range ending at `now`.
+    /// The api reads up to 48 hours or whole weeks, `None` for the other lengths. `ytd` is the exception,
+    /// it's rounded up to the next hour or week, so it can be up to a week longer than [`TimeRange::seconds_at`].
+    pub fn invictus_range_at(&self, now: u64) -> Option<String> {
+        let seconds = self.seconds_at(now)?;
+        if seconds <= u64::from(INVICTUS_MAX_HOURS) * HOUR {
+            Some(format!("{}h", seconds.div_ceil(HOUR).max(1)))
+        } else if seconds.is_multiple_of(WEEK) || *self == TimeRange::YearToDate {
+            Some(format!("{}w", seconds.div_ceil(WEEK)))
+        } else {
+            None
+        }
+    }
+
+    /// Human readable name, eg.: `12 hours`.
+    pub fn label(&self) -> String {
+        let (count, unit) = match *self {
+            TimeRange::Hours(count) => (count, "hour"),
+            TimeRange::Days(count) => (count, "day"),
+            TimeRange::Weeks(count) => (count, "week"),
+            TimeRange::Months(count) => (count, "month"),
+            TimeRange::Years(count) => (count, "year"),
+            TimeRange::YearToDate => return "year to date".to_string(),
+        };
+        format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" })
+    }
+}
+
+impl FromStr for TimeRange {
+    type Err = TimeRangeError;
+
+    fn from_str(range: &str) -> Result<Self, Self::Err> {
+        let range = range.trim().to_lowercase();
+        if range == "ytd" {
+            return Ok(TimeRange::YearToDate)
+        }
+        let invalid = || TimeRangeError::Invalid(range.clone());
+        let unit = range.chars().last().ok_or_else(invalid)?;
+        let count: u32 = range[..range.len() - unit.len_utf8()].parse().map_err(|_| invalid())?;
+        let (time_range, max) = match unit {
+            'h' => (TimeRange::Hours(count), 48),
+            'd' => (TimeRange::Days(count), 31),
+            'w' => (TimeRange::Weeks(count), 52),
+            'm' => (TimeRange::Months(count), 12),
+            'y' => (TimeRange::Years(count), 3),
+            _ => return Err(invalid()),
+        };
+        if count == 0 || count > max {
+            return Err(TimeRangeError::OutOfLimits { range, max, unit })
+        }
+        Ok(time_range)
+    }
+}
+
+impl TryFrom<String> for TimeRange {
+    type Error = TimeRangeError;
+
+    fn try_from(range: String) -> Result<Self, Self::Error> {
+        range.parse()
+    }
+}
+
+impl fmt::Display for TimeRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeRange::Hours(count) => write!(f, "{}h", count),
+            TimeRange::Days(count) => write!(f, "{}d", count),
+            TimeRange::Weeks(count) => write!(f, "{}w", count),
+            TimeRange::Months(count) => write!(f, "{}m", count),
+            TimeRange::Years(count) => write!(f, "{}y", count),
+            TimeRange::YearToDate => f.write_str("ytd"),
+        }
+    }
+}
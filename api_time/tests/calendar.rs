@@ -0,0 +1,72 @@
+use api_time::calendar::{start_of_day, start_of_month, start_of_week, start_of_year};
+use api_time::{duration, Clock, FixedClock, SystemClock, Tz};
+
+// 2021-03-14 12:00:00 UTC, a Sunday, daylight saving starts in New York at 2am
+const MARCH_14_2021_NOON: u64 = 1_615_723_200;
+// 2021-03-15 12:00:00 UTC, a Monday
+const MARCH_15_2021_NOON: u64 = 1_615_809_600;
+
+#[test]
+fn utc_calendar_starts() {
+    assert_eq!(start_of_day(MARCH_15_2021_NOON, Tz::UTC), Some(1_615_766_400));
+    assert_eq!(start_of_week(MARCH_15_2021_NOON, Tz::UTC), Some(1_615_766_400));
+    assert_eq!(start_of_month(MARCH_15_2021_NOON, Tz::UTC), Some(1_614_556_800));
+    assert_eq!(start_of_year(MARCH_15_2021_NOON, Tz::UTC), Some(1_609_459_200));
+}
+
+#[test]
+fn calendar_starts_follow_daylight_saving() {
+    let new_york: Tz = "America/New_York".parse().unwrap();
+    // midnight of the 14th is still EST, of the 15th EDT
+    assert_eq!(start_of_day(MARCH_14_2021_NOON, new_york), Some(1_615_698_000));
+    assert_eq!(start_of_day(MARCH_15_2021_NOON, new_york), Some(1_615_780_800));
+    assert_eq!(start_of_week(MARCH_14_2021_NOON, new_york), Some(1_615_179_600));
+    assert_eq!(start_of_month(MARCH_15_2021_NOON, new_york), Some(1_614_574_800));
+}
+
+#[test]
+fn calendar_starts_ahead_of_utc() {
+    let tokyo: Tz = "Asia/Tokyo".parse().unwrap();
+    // 2020-12-31 20:00 UTC is already 2021 in Tokyo
+    assert_eq!(start_of_year(1_609_444_800, tokyo), Some(1_609_426_800));
+}
+
+#[test]
+fn day_without_a_midnight() {
+    // Havana skipped from midnight to 1am on 2021-03-14
+    let havana: Tz = "America/Havana".parse().unwrap();
+    assert_eq!(start_of_day(MARCH_14_2021_NOON, havana), Some(1_615_698_000));
+}
+
+#[test]
+fn calendar_starts_past_the_representable_dates() {
+    assert_eq!(start_of_day(u64::MAX, Tz::UTC), None);
+    assert_eq!(start_of_week(u64::MAX, Tz::UTC), None);
+    assert_eq!(start_of_month(i64::MAX as u64, Tz::UTC), None);
+    assert_eq!(start_of_year(u64::MAX, Tz::UTC), None);
+}
+
+#[test]
+fn checked_durations() {
+    assert_eq!(duration::hours(2), Some(7_200));
+    assert_eq!(duration::days(3), Some(259_200));
+    assert_eq!(duration::weeks(1), Some(604_800));
+    assert_eq!(duration::months(1), Some(2_419_200));
+    assert_eq!(duration::years(1), Some(31_449_600));
+    assert_eq!(duration::years(u64::MAX), None);
+    assert_eq!(duration::before(1_000_000, duration::weeks(1).unwrap()), Some(1_000_000 - 604_800));
+    // a day before the first day of the unix epoch
+    assert_eq!(duration::before(3_600, duration::days(1).unwrap()), None);
+}
+
+#[test]
+fn fixed_clock_moves_when_told() {
+    let clock = FixedClock::new(MARCH_15_2021_NOON);
+    let shared = clock.clone();
+    assert_eq!(clock.now(), MARCH_15_2021_NOON);
+    shared.advance(60);
+    assert_eq!(clock.now(), MARCH_15_2021_NOON + 60);
+    shared.set(0);
+    assert_eq!(clock.now(), 0);
+    assert!(SystemClock.now() > MARCH_15_2021_NOON);
+}
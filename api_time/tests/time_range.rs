@@ -0,0 +1,103 @@
+use api_time::{TimeRange, TimeRangeError, DEFAULT_RANGES};
+
+// 2021-03-15 12:00:00 UTC
+const MARCH_15_2021_NOON: u64 = 1_615_809_600;
+
+#[test]
+fn parses_the_supported_ranges() {
+    assert_eq!("1h".parse(), Ok(TimeRange::Hours(1)));
+    assert_eq!("12H".parse(), Ok(TimeRange::Hours(12)));
+    assert_eq!(" 1d ".parse(), Ok(TimeRange::Days(1)));
+    assert_eq!("1w".parse(), Ok(TimeRange::Weeks(1)));
+    assert_eq!("1m".parse(), Ok(TimeRange::Months(1)));
+    assert_eq!("1y".parse(), Ok(TimeRange::Years(1)));
+    assert_eq!("YTD".parse(), Ok(TimeRange::YearToDate));
+}
+
+#[test]
+fn rejects_invalid_ranges() {
+    for range in &["", "h", "d1", "-1d", "1.5h", "1x", "1 h", "ytd1"] {
+        assert!(matches!(range.parse::<TimeRange>(), Err(TimeRangeError::Invalid(_))), "{}", range);
+    }
+}
+
+#[test]
+fn validates_limits() {
+    assert_eq!("0h".parse::<TimeRange>(), Err(TimeRangeError::OutOfLimits { range: "0h".into(), max: 48, unit: 'h' }));
+    assert!("53w".parse::<TimeRange>().is_err());
+    assert!("13m".parse::<TimeRange>().is_err());
+    assert_eq!("52w".parse(), Ok(TimeRange::Weeks(52)));
+    assert_eq!("31d".parse(), Ok(TimeRange::Days(31)));
+    assert!(matches!("32d".parse::<TimeRange>(), Err(TimeRangeError::OutOfLimits { .. })));
+}
+
+#[test]
+fn converts_to_seconds() {
+    assert_eq!(TimeRange::Hours(12).seconds_at(MARCH_15_2021_NOON), Some(12 * 3600));
+    assert_eq!(TimeRange::Days(1).seconds_at(MARCH_15_2021_NOON), Some(86_400));
+    assert_eq!(TimeRange::Weeks(2).seconds_at(MARCH_15_2021_NOON), Some(14 * 86_400));
+    assert_eq!(TimeRange::Months(1).seconds_at(MARCH_15_2021_NOON), Some(28 * 86_400));
+    assert_eq!(TimeRange::Years(1).seconds_at(MARCH_15_2021_NOON), Some(364 * 86_400));
+    assert_eq!(TimeRange::YearToDate.seconds_at(MARCH_15_2021_NOON), Some(73 * 86_400 + 12 * 3600));
+}
+
+#[test]
+fn range_starts() {
+    assert_eq!(TimeRange::Days(1).start_at(MARCH_15_2021_NOON), Some(MARCH_15_2021_NOON - 86_400));
+    assert_eq!(TimeRange::YearToDate.start_at(MARCH_15_2021_NOON), Some(1_609_459_200));
+    assert_eq!(TimeRange::Years(1).start_at(86_400), None);
+    assert_eq!(TimeRange::YearToDate.seconds_at(u64::MAX), None);
+    assert_eq!(TimeRange::YearToDate.start_at(u64::MAX), None);
+}
+
+#[test]
+fn invictus_range_parameter() {
+    let ranges: Vec<String> = DEFAULT_RANGES.iter().map(|range| range.invictus_range_at(MARCH_15_2021_NOON).unwrap()).collect();
+    assert_eq!(ranges, vec!["1h", "12h", "24h", "1w", "4w", "52w"]);
+    // 73.5 days into the year
+    assert_eq!(TimeRange::YearToDate.invictus_range_at(MARCH_15_2021_NOON).as_deref(), Some("11w"));
+    // 90 minutes into the year
+    assert_eq!(TimeRange::YearToDate.invictus_range_at(1_609_459_200 + 5_400).as_deref(), Some("2h"));
+    assert_eq!(TimeRange::Days(2).invictus_range_at(MARCH_15_2021_NOON).as_deref(), Some("48h"));
+    assert_eq!(TimeRange::Days(14).invictus_range_at(MARCH_15_2021_NOON).as_deref(), Some("2w"));
+    assert_eq!(TimeRange::Years(3).invictus_range_at(MARCH_15_2021_NOON).as_deref(), Some("156w"));
+}
+
+#[test]
+fn invictus_range_of_lengths_the_api_cant_read() {
+    for range in &["3d", "5d", "27d", "31d"] {
+        assert_eq!(range.parse::<TimeRange>().unwrap().invictus_range_at(MARCH_15_2021_NOON), None, "{}", range);
+    }
+}
+
+#[test]
+fn invictus_range_matches_the_seconds() {
+    let ranges = ["1h", "48h", "1d", "2d", "7d", "28d", "1w", "52w", "1m", "12m", "1y", "3y"];
+    for range in ranges.iter().map(|range| range.parse::<TimeRange>().unwrap()) {
+        let seconds = range.seconds_at(MARCH_15_2021_NOON).unwrap();
+        let parameter = range.invictus_range_at(MARCH_15_2021_NOON).unwrap();
+        let (count, unit) = parameter.split_at(parameter.len() - 1);
+        let unit_seconds = if unit == "h" { 3_600 } else { 604_800 };
+        assert_eq!(count.parse::<u64>().unwrap() * unit_seconds, seconds, "{}", range);
+    }
+}
+
+#[test]
+fn labels() {
+    let short: Vec<String> = DEFAULT_RANGES.iter().map(ToString::to_string).collect();
+    assert_eq!(short, vec!["1h", "12h", "1d", "1w", "1m", "1y"]);
+    assert_eq!(TimeRange::Hours(12).label(), "12 hours");
+    assert_eq!(TimeRange::Days(1).label(), "1 day");
+    assert_eq!(TimeRange::YearToDate.label(), "year to date");
+}
+
+#[test]
+fn deserializes_from_config_strings() {
+    #[derive(serde_derive::Deserialize)]
+    struct Config {
+        playing: TimeRange,
+    }
+    let config: Config = toml::from_str("playing = \"1d\"").unwrap();
+    assert_eq!(config.playing, TimeRange::Days(1));
+    assert!(toml::from_str::<Config>("playing = \"forever\"").is_err());
+}
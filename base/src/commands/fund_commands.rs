@@ -1,44 +1,42 @@
 use invictus_api::*;
+use api_time::{TimeRange, DEFAULT_RANGES};
 use serenity::framework::standard::{macros::command, Args, CommandResult};
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 
 use thousands::Separable;
 
+use crate::utils;
+
 #[command]
 pub async fn nav(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
     
-    let mut fund_to_check = String::new();
-    let api_response = api_general().await?;
-    let funds_general_raw = api_response.data;
-    let mut fund_found = false;
-
-    if args.len() == 0 {
-        fund_to_check  = "crypto10".to_string();
-    } else if args.len() == 1 {
+    let fund_to_check = if args.is_empty() {
+        FundId::new("crypto10")
+    } else {
         let arg = args.single::<String>()?;
-        match normalize_fund_name(&arg) {
-            Ok(checked_name) => {
-                fund_to_check = checked_name;
-            }
-            Err(_) => {
+        match utils::lookup_fund(ctx, &arg).await {
+            Some(checked_fund) => checked_fund,
+            None => {
                 msg.reply(&ctx.http, "Unknown fund").await?;
                 return Ok(())
             }
-        };
-    }
+        }
+    };
+    let api_response = utils::invictus(ctx).await.funds().await?;
+    let funds_general_raw = api_response.data;
+    let mut fund_found = false;
     
     for fund in funds_general_raw {
-        if fund.name == fund_to_check {
-            let mut nav = fund.nav_per_token;
-            nav.truncate(5);
+        if fund.name == fund_to_check.as_str() {
+            let nav = fund.nav_per_token.with_precision(3);
             msg.channel_id.say(&ctx.http, format!("***{} NAV:***\n**{}$**", fund_to_check, nav)).await?;
             fund_found = true;
         }
     }
 
     if !fund_found {
-        msg.channel_id.say(&ctx.http, format!("Cannot find fund in received data")).await?;
+        msg.channel_id.say(&ctx.http, "Cannot find fund in received data").await?;
     }
     Ok(())
 }
@@ -46,84 +44,104 @@ pub async fn nav(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
 #[allow(unused_assignments)]
 #[command]
 pub async fn stats(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let mut fund_to_check = String::new();
-    if args.len() == 0 {
-        fund_to_check  = "crypto10".to_string();
-    } else if args.len() == 1 {
+    let mut fund_to_check = FundId::new("crypto10");
+    let mut list_all = false;
+    if args.len() > 2 {
+        msg.reply(&ctx.http, "Too many arguments, please check `-help`").await?;
+        return Ok(())
+    }
+    if !args.is_empty() {
         let arg = args.single::<String>()?;
-        match normalize_fund_name(&arg) {
-            Ok(checked_name) => {
-                fund_to_check = checked_name;
+        match utils::lookup_fund(ctx, &arg).await {
+            Some(checked_fund) => {
+                fund_to_check = checked_fund;
             }
-            Err(_) => {
+            None => {
                 msg.reply(&ctx.http, "Unknown fund").await?;
                 return Ok(())
             }
         };
-    } else {
-        msg.reply(&ctx.http, "Too many arguments, please check `-help`").await?;
-        return Ok(())
+    }
+    if !args.is_empty() {
+        if args.single::<String>()?.to_lowercase() != "all" {
+            msg.reply(&ctx.http, "Unknown option, please check `-help`").await?;
+            return Ok(())
+        }
+        list_all = true;
     }
     
-    let mut api_response = FundPie::fund_pie(&fund_to_check).await?;
-    api_response.stablecoin_summary();
-    api_response.remove_small_assets();
-    let fund_nav = FundNav::fund_nav(&fund_to_check).await?;
-    let fund_net_value = (fund_nav.net_asset_value().parse::<f64>().unwrap()) as i64;
-
-    let mut summary = String::from(format!("*{}*\n", fund_to_check));
+    let invictus_client = utils::invictus(ctx).await;
+    let mut api_response = invictus_client.fund_pie(&fund_to_check).await?;
+    let asset_classes = utils::asset_classes(ctx).await;
+    let class_totals = api_response.grouped_by_class(&asset_classes);
+    let threshold = if list_all { Decimal::ZERO } else { utils::small_asset_threshold(ctx).await };
+    api_response.remove_small_assets(&asset_classes, threshold);
+    let fund_nav = invictus_client.fund_nav(&fund_to_check).await?;
+    let fund_net_value = fund_nav.net_asset_value().whole();
+
+    let mut summary = format!("*{}*\n", fund_to_check);
     summary.push_str(&format!("**Fund Net Value**: ${}\n", fund_net_value.separate_with_commas()));
     for asset in api_response.assets {
-        let asset_usd = (asset.value.parse::<f64>().unwrap()) as i64;
+        let asset_usd = asset.value.whole();
         summary.push_str(&format!("**{} {}%** ${}\n", asset.ticker, asset.percentage, asset_usd.separate_with_commas()));
     }
-    msg.channel_id.say(&ctx.http, format!("{}", summary)).await?;
+    summary.push_str("*Asset classes*\n");
+    for class_total in class_totals {
+        summary.push_str(&format!("**{} {}%** ${}\n", class_total.class, class_total.percentage.with_precision(2), class_total.value.whole().separate_with_commas()));
+    }
+    msg.channel_id.say(&ctx.http, summary).await?;
     Ok(())
 }
 
 #[command]
 pub async fn perf(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
     
-    let default_ranges = vec!["1h", "12h", "24h", "1w", "4w", "52w"];
-    let mut fund_name = String::new();
-    let mut range = String::new();
+    let mut fund_name = FundId::new("crypto10");
+    let mut ranges = DEFAULT_RANGES.to_vec();
     let mut return_message = String::new();
-    if args.len() == 0 {
-        fund_name  = "crypto10".to_string();
-        for range in default_ranges {
-            let api_response = fund_perf(&fund_name, range).await?;
-            return_message.push_str(&format!("**{} {}%**\n", range, api_response))
-        }
-    } else if args.len() == 1 {
+    let invictus_client = utils::invictus(ctx).await;
+    if args.len() == 1 {
         let arg = args.single::<String>()?;
-        match normalize_fund_name(&arg) {
-            Ok(checked_name) => {
-                fund_name = checked_name;
-                for range in default_ranges {
-                    let api_response = fund_perf(&fund_name, range).await?;
-                    return_message.push_str(&format!("**{} {}%**\n", range, api_response))
+        match utils::lookup_fund(ctx, &arg).await {
+            Some(checked_fund) => fund_name = checked_fund,
+            None => match arg.parse::<TimeRange>() {
+                Ok(range) => ranges = vec![range],
+                Err(e) => {
+                    msg.reply_ping(&ctx.http, format!("Sorry I didn't understand *{}*\n{}", arg, e)).await?;
+                    return Ok(())
                 }
             }
-            Err(_) => {
-                fund_name  = "crypto10".to_string();
-                let api_response = fund_perf(&fund_name, &arg).await?;
-                return_message.push_str(&format!("**{} {}%**\n", range, api_response));
-            }
         };
     } else if args.len() == 2 {
-        fund_name  = args.single::<String>()?;
-        range  = args.single::<String>()?;
+        let arg = args.single::<String>()?;
+        let range = args.single::<String>()?;
 
-        match normalize_fund_name(&fund_name) {
-            Ok(checked_name) => fund_name = checked_name,
-            Err(_) => {
-                msg.reply_ping(&ctx.http, format!("Sorry I didn't understand *{}*\n", fund_name)).await?;
+        match utils::lookup_fund(ctx, &arg).await {
+            Some(checked_fund) => fund_name = checked_fund,
+            None => {
+                msg.reply_ping(&ctx.http, format!("Sorry I didn't understand *{}*\n", arg)).await?;
                 return Ok(())
             }
         };
+        match range.parse::<TimeRange>() {
+            Ok(range) => ranges = vec![range],
+            Err(e) => {
+                msg.reply_ping(&ctx.http, e.to_string()).await?;
+                return Ok(())
+            }
+        }
+    }
 
-        let api_response = fund_perf(&fund_name, &range).await?;
-        return_message.push_str(&format!("**{} {}%**\n", range, api_response))
+    for range in ranges {
+        let api_response = match invictus_client.fund_perf(&fund_name, range).await {
+            Ok(perf) => perf.with_precision(2),
+            Err(e @ InvictusError::RangeNotServed(_)) => {
+                msg.reply_ping(&ctx.http, utils::api_error_reply(&e)).await?;
+                return Ok(())
+            }
+            Err(e) => return Err(e.into()),
+        };
+        return_message.push_str(&format!("**{} {}%**\n", range.label(), api_response))
     }
     
     msg.channel_id.say(&ctx.http, format!("*{}*\n{}", fund_name, return_message)).await?;
@@ -150,9 +168,9 @@ pub async fn perf(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
 #[command]
 pub async fn info(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
 
-    let fund_nav = FundNav::fund_nav("c10").await?;
-    let net_value_raw: i64 = (fund_nav.net_asset_value().parse::<f64>().unwrap()) as i64;
-    let net_value = String::from(format!("**Fund Net Value**: ${}\n", net_value_raw.separate_with_commas()));
+    let fund_nav = utils::invictus(ctx).await.fund_nav(&FundId::new("crypto10")).await?;
+    let net_value_raw: i64 = fund_nav.net_asset_value().whole();
+    let net_value = format!("**Fund Net Value**: ${}\n", net_value_raw.separate_with_commas());
     let _ = msg.channel_id.send_message(&ctx.http, |m| {
         m.embed(|e| {
             e.title(net_value);
@@ -176,9 +194,10 @@ pub async fn help(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
     perf_help.push_str("`-perf <timerange>` returns the C10 fund preformance summary for the given timerange.\n");
     perf_help.push_str("`-perf <ticker>` returns the given fund preformance summary for the past 1 year.\n");
     perf_help.push_str("`-perf <ticker> <timerange>` returns the given fund performance summary for the given timerange.\n");
+    perf_help.push_str("Time ranges: `<n>h` up to 48h, `<n>d` up to 31d, `<n>w` up to 52w, `<n>m` up to 12m, `<n>y` up to 3y and `ytd`, Invictus api funds read days up to 2d or in whole weeks.\n");
     perf_help.push_str("(ex):\n`-perf c20` for C20 fund performance summary of past 1 year\n");
     perf_help.push_str("`-perf c20 4w` for C20 fund performance summary of past 4 weeks \n");
-    perf_help.push_str("`-perf iml 52w` for IML fund performance summary of past 52 weeks / 1 year \n");
+    perf_help.push_str("`-perf iml 1y` for IML fund performance summary of past 1 year \n");
     let _ = msg.channel_id.send_message(&ctx.http, |m| {
         m.embed(|e| {
             e.title("C10 bot help");
@@ -187,7 +206,7 @@ pub async fn help(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
             e.field("-help", "This help message.", false);
             e.field("-info", "Useful links.", false);
             e.field("-nav", "Current token value. \neg.: `-nav` `-nav <ticker>`", false);
-            e.field("-stats", "Current fund asset allocation statistics. \neg.:`-stats` `-stats <ticker>`", false);
+            e.field("-stats", "Current fund asset allocation statistics. \nAssets at or below the threshold are summed into one row, `all` lists every asset.\neg.:`-stats` `-stats <ticker>` `-stats <ticker> all`", false);
             e.field("-perf", perf_help, false);
             e
         });
@@ -8,10 +8,10 @@ use serenity::{
     client::bridge::gateway::ShardManager,
     framework::{
         StandardFramework,
-        standard::macros::group,
+        standard::{CommandResult, macros::{group, hook}},
     },
     http::Http,
-    model::{event::ResumedEvent, gateway::Ready, id::GuildId},
+    model::{channel::Message, event::ResumedEvent, gateway::Ready, id::GuildId},
     prelude::*,
 };
 
@@ -28,6 +28,8 @@ use commands::{
 };
 mod utils;
 
+use invictus_api::{FundRegistry, InvictusError};
+
 #[group]
 #[commands(quit, re)]
 struct General;
@@ -64,7 +66,7 @@ impl EventHandler for Handler {
             let ( fund_ticker, update_frequency, playing) = {
                 let data_read_lock = ctx.data.read().await;
                 let config = data_read_lock.get::<utils::Config>().expect("Expected Config in TypeMap.");
-                (config.fund_ticker.clone(), config.update_frequency.clone(), config.playing.clone())
+                (config.fund_ticker.clone(), config.update_frequency, config.playing)
     
             };
             // let (fund_ticker_clone, update_frequency_clone) = (fund_ticker.clone(), update_frequency.clone());
@@ -77,7 +79,7 @@ impl EventHandler for Handler {
 
             let ctx2 = Arc::clone(&ctx);
             tokio::spawn(async move {
-                utils::update_nick_and_activity(Arc::clone(&ctx2), &fund_ticker, guilds, update_frequency, &playing).await;
+                utils::update_nick_and_activity(Arc::clone(&ctx2), &fund_ticker, guilds, update_frequency, playing).await;
             });
 
             // Now that the loop is running, we set the bool to true
@@ -87,10 +89,31 @@ impl EventHandler for Handler {
 }
 
 
+#[hook]
+async fn after(ctx: &Context, msg: &Message, command_name: &str, command_result: CommandResult) {
+    if let Err(why) = command_result {
+        info!("Command '{}' returned error {}", command_name, why);
+        if let Some(api_error) = why.downcast_ref::<InvictusError>() {
+            if let Err(e) = msg.reply(&ctx.http, utils::api_error_reply(api_error)).await {
+                info!("failed to reply with the api error\n{}", e);
+            }
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
     let config: utils::Config = utils::loadconfig().expect("Can't load config file: botconfig.toml. Please make sure you have one next to the executable and it's correct.");
     info!("Botconfig loaded {:?}", &config);
+    if !config.api.init_request_policy() {
+        error!("The request policy was already in use, the request_policy settings in botconfig.toml are ignored.");
+    }
+    let invictus_client = config.api.invictus_client().expect("Can't create the invictus api client, please check the api settings in botconfig.toml.");
+    let uniswap_client = config.api.uniswap_client().expect("Can't create the uniswap client, please check the api settings in botconfig.toml.");
+    let price_sources = config.api.price_sources(&config.fund.price_sources, &invictus_client, &uniswap_client).expect("Can't create the fund price sources, please check the price_sources settings in botconfig.toml.");
+    let fund_aliases = config.fund.load_fund_aliases().expect("Can't load the fund aliases file set in botconfig.toml.");
+    let fund_registry = Arc::new(RwLock::new(FundRegistry::builtin().with_aliases(&fund_aliases)));
+    let registry_refresh = config.fund.registry_refresh();
 
     let filter = EnvFilter::from_default_env()
         .add_directive(LevelFilter::INFO.into());// Set the base level when not matched by other directives to INFO.
@@ -119,6 +142,7 @@ async fn main() {
                     .owners(owners)
                     .prefix(&config.prefix)
                     .allowed_channels(config.allowed_channels.clone().into_iter().collect()))
+        .after(after)
         .group(&GENERAL_GROUP);
 
     let mut client = Client::builder(&config.bot_token)
@@ -132,9 +156,17 @@ async fn main() {
     {
         let mut data = client.data.write().await;
         data.insert::<ShardManagerContainer>(client.shard_manager.clone());
+        data.insert::<utils::FundConfigContainer>(config.fund.clone());
         data.insert::<utils::Config>(config);
+        data.insert::<utils::InvictusContainer>(invictus_client.clone());
+        data.insert::<utils::PriceSourcesContainer>(price_sources);
+        data.insert::<utils::RegistryContainer>(Arc::clone(&fund_registry));
     }
 
+    tokio::spawn(async move {
+        utils::refresh_fund_registry(invictus_client, fund_registry, fund_aliases, registry_refresh).await;
+    });
+
     let shard_manager = client.shard_manager.clone();
 
     tokio::spawn(async move {
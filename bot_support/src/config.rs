@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use api_policy::{PolicyConfig, RequestPolicy};
+use eth_rpc::EthRpcClient;
+use etherscan_io_api::{BlockEstimator, EtherscanClient, EtherscanClientBuilder};
+use invictus_api::{AssetClasses, CacheTtl, Decimal, FundAliases, InvictusClient, SMALL_ASSET_THRESHOLD};
+use price_source::{PriceSources, SourceChain};
+use serde_derive::Deserialize;
+use serenity::model::id::ChannelId;
+use uniswap_v2_api::{Dex, DexVersion, UniswapV2Client, UniswapV3Client};
+
+/// Block anchors file of the block estimator when the bot config doesn't set one.
+pub const DEFAULT_ANCHORS_FILE: &str = "block_anchors.json";
+/// Seconds between the fund registry refreshes when the bot config doesn't set them.
+pub const DEFAULT_REGISTRY_REFRESH: u64 = 3600;
+
+/// Api settings of the bot config, flattened into the `Config` of every bot.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiConfig {
+    pub invictus_api_url: Option<String>,
+    /// Request timeout in seconds of every api client.
+    pub api_timeout: Option<u64>,
+    pub uniswap_url: Option<String>,
+    pub uniswap_v3_url: Option<String>,
+    pub uniswap_liquidity_floor: Option<f64>,
+    pub etherscan_api_url: Option<String>,
+    pub etherscan_api_key: Option<String>,
+    pub block_anchors_file: Option<String>,
+    pub eth_rpc_url: Option<String>,
+    #[serde(default)]
+    pub api_cache: CacheTtl,
+    #[serde(default)]
+    pub request_policy: PolicyConfig,
+}
+
+impl ApiConfig {
+    /// Makes the `[request_policy]` table the process wide policy, call it before building the clients.
+    /// Returns false if a client already used the default policy, the table is ignored then.
+    pub fn init_request_policy(&self) -> bool {
+        RequestPolicy::init_global(self.request_policy.clone())
+    }
+
+    pub fn invictus_client(&self) -> Result<InvictusClient> {
+        let mut builder = InvictusClient::builder().cache_ttl(self.api_cache);
+        if let Some(url) = &self.invictus_api_url {
+            builder = builder.base_url(url);
+        }
+        if let Some(timeout) = self.timeout() {
+            builder = builder.timeout(timeout);
+        }
+        Ok(builder.build()?)
+    }
+
+    /// Etherscan client of the api settings, the environment for the unset ones.
+    pub fn etherscan_client(&self) -> Result<EtherscanClient> {
+        let mut builder = EtherscanClientBuilder::from_env();
+        if let Some(url) = &self.etherscan_api_url {
+            builder = builder.base_url(url);
+        }
+        if let Some(api_key) = &self.etherscan_api_key {
+            builder = builder.api_key(api_key);
+        }
+        if let Some(timeout) = self.timeout() {
+            builder = builder.timeout(timeout);
+        }
+        Ok(builder.build()?)
+    }
+
+    /// Block estimator of the uniswap performance, its anchors are saved to the block anchors file.
+    pub fn block_estimator(&self) -> Result<BlockEstimator> {
+        let anchors_file = self.block_anchors_file.as_deref().unwrap_or(DEFAULT_ANCHORS_FILE);
+        Ok(BlockEstimator::builder().etherscan(self.etherscan_client()?).cache_file(anchors_file).build()?)
+    }
+
+    pub fn uniswap_client(&self) -> Result<UniswapV2Client> {
+        let mut builder = UniswapV2Client::builder().block_estimator(self.block_estimator()?);
+        if let Some(url) = &self.uniswap_url {
+            builder = builder.subgraph_url(url);
+        }
+        if let Some(liquidity_floor) = self.uniswap_liquidity_floor {
+            builder = builder.liquidity_floor(liquidity_floor);
+        }
+        if let Some(timeout) = self.timeout() {
+            builder = builder.timeout(timeout);
+        }
+        builder.build()
+    }
+
+    /// Uniswap V3 client sharing the block estimator of the V2 client.
+    pub fn uniswap_v3_client(&self, uniswap_client: &UniswapV2Client) -> Result<UniswapV3Client> {
+        let mut builder = UniswapV3Client::builder().block_estimator(uniswap_client.block_estimator().clone());
+        if let Some(url) = &self.uniswap_v3_url {
+            builder = builder.subgraph_url(url);
+        }
+        if let Some(liquidity_floor) = self.uniswap_liquidity_floor {
+            builder = builder.liquidity_floor(liquidity_floor);
+        }
+        if let Some(timeout) = self.timeout() {
+            builder = builder.timeout(timeout);
+        }
+        builder.build()
+    }
+
+    /// Client of the uniswap version, V2 is the given client.
+    pub fn dex_client(&self, dex: DexVersion, uniswap_client: &UniswapV2Client) -> Result<Arc<dyn Dex>> {
+        match dex {
+            DexVersion::V2 => Ok(Arc::new(uniswap_client.clone())),
+            DexVersion::V3 => Ok(Arc::new(self.uniswap_v3_client(uniswap_client)?)),
+        }
+    }
+
+    /// Ethereum node client of the rpc price sources, the local node unless the bot config sets a url.
+    pub fn eth_rpc_client(&self) -> Result<EthRpcClient> {
+        let mut builder = EthRpcClient::builder();
+        if let Some(url) = &self.eth_rpc_url {
+            builder = builder.url(url);
+        }
+        if let Some(timeout) = self.timeout() {
+            builder = builder.timeout(timeout);
+        }
+        Ok(builder.build()?)
+    }
+
+    /// Price sources of the config entries, the invictus api for the funds without one.
+    pub fn price_sources(&self, entries: &HashMap<String, SourceChain>, invictus_client: &InvictusClient, uniswap_client: &UniswapV2Client) -> Result<PriceSources> {
+        let uniswap_v3_client = self.uniswap_v3_client(uniswap_client)?;
+        Ok(PriceSources::new(entries, invictus_client.clone(), Arc::new(uniswap_client.clone()), Arc::new(uniswap_v3_client), self.eth_rpc_client()?)?)
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.api_timeout.map(Duration::from_secs)
+    }
+}
+
+/// Fund price settings of the bot config, flattened into the `Config` of the fund nav bots.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FundConfig {
+    /// The activity shows the time weighted average price performance instead of the spot price one.
+    #[serde(default)]
+    pub twap_activity: bool,
+    /// Toml file with extra fund aliases.
+    pub fund_aliases: Option<String>,
+    /// Seconds between the fund registry refreshes.
+    pub registry_refresh: Option<u64>,
+    #[serde(default)]
+    pub price_sources: HashMap<String, SourceChain>,
+    /// Percent the navs of a fund's price sources may differ by.
+    pub divergence_threshold: Option<Decimal>,
+    pub divergence_alert_channel: Option<ChannelId>,
+    /// Asset classes of the `-stats` class breakdown.
+    #[serde(default)]
+    pub asset_classes: AssetClasses,
+    /// Allocation in percent at or below which `-stats` sums the assets into one row.
+    pub small_asset_threshold: Option<Decimal>,
+}
+
+impl FundConfig {
+    /// Aliases of the fund aliases file, none without one.
+    pub fn load_fund_aliases(&self) -> Result<FundAliases> {
+        match &self.fund_aliases {
+            Some(path) => Ok(FundAliases::load(path)?),
+            None => Ok(FundAliases::default()),
+        }
+    }
+
+    pub fn registry_refresh(&self) -> u64 {
+        self.registry_refresh.unwrap_or(DEFAULT_REGISTRY_REFRESH)
+    }
+
+    pub fn small_asset_threshold(&self) -> Decimal {
+        self.small_asset_threshold.unwrap_or(SMALL_ASSET_THRESHOLD)
+    }
+}
@@ -0,0 +1,200 @@
+//! Discord side of the fund nav bots: the bot data containers, the nickname and activity loop and the fund lookups.
+
+use std::sync::Arc;
+
+use api_time::TimeRange;
+use invictus_api::{AssetClasses, Decimal, FundAliases, FundId, FundRegistry, InvictusClient, InvictusError, Precision};
+use price_source::{FailoverSource, NavReadings, PriceSource, PriceSources, DIVERGENCE_THRESHOLD};
+use serenity::{
+    model::gateway::Activity,
+    model::id::GuildId,
+    prelude::*,
+};
+use tokio::time::{sleep, Duration};
+use tracing::{debug, info, warn};
+use uniswap_v2_api::UniswapV2Client;
+
+use crate::FundConfig;
+
+pub struct FundConfigContainer;
+
+impl TypeMapKey for FundConfigContainer {
+    type Value = FundConfig;
+}
+
+pub struct InvictusContainer;
+
+impl TypeMapKey for InvictusContainer {
+    type Value = InvictusClient;
+}
+
+pub struct UniswapContainer;
+
+impl TypeMapKey for UniswapContainer {
+    type Value = UniswapV2Client;
+}
+
+pub struct PriceSourcesContainer;
+
+impl TypeMapKey for PriceSourcesContainer {
+    type Value = PriceSources;
+}
+
+pub struct RegistryContainer;
+
+impl TypeMapKey for RegistryContainer {
+    type Value = Arc<RwLock<FundRegistry>>;
+}
+
+/// Keeps the nickname on the fund nav and the activity on its performance over `playing`, every `update_frequency` seconds.
+pub async fn update_nick_and_activity(ctx: Arc<Context>, fund_ticker: &str, guilds: Vec<GuildId>, update_frequency: u64, playing: TimeRange) {
+    let twap = twap_activity(&ctx).await;
+    let mut diverged = false;
+    loop {
+        let source = match price_chain(&ctx, fund_ticker).await {
+            Some(source) => source,
+            None => {
+                info!("fund name {} not found in the fund registry", &fund_ticker);
+                sleep(Duration::from_secs(update_frequency)).await;
+                continue;
+            }
+        };
+        let perf = if twap {
+            source.twap_perf(playing).await
+        } else {
+            source.perf(playing).await
+        };
+        let perf_percent = match perf {
+            Ok(percent) => percent.with_precision(2),
+            Err(e) => {
+                info!("fund_perf {} api call failed on every price source\n{}", &fund_ticker, e.to_string());
+                sleep(Duration::from_secs(update_frequency)).await;
+                continue;
+            },
+        };
+        let trend = if perf_percent.contains('-') {"⬂"} else {"⬀"};
+
+        let readings = source.nav_readings().await;
+        for (backend, e) in readings.failures() {
+            info!("nav {} {} api call failed\n{}", &fund_ticker, backend, e);
+        }
+        diverged = check_divergence(&ctx, fund_ticker, &readings, diverged).await;
+        // the nickname keeps the last nav when every source failed
+        if let Some(fund_nav) = readings.nav() {
+            let fund_nav = fund_nav.with_precision(3);
+            for server in guilds.clone() {
+                if let Err(e) = server.edit_nickname(&ctx.http, Some(&format!("{} ${} {}",fund_ticker, fund_nav, trend))).await {
+                    info!("{} failed to update nick with nav\n{}", &fund_ticker, e);
+                    sleep(Duration::from_secs(update_frequency)).await;
+                    continue;
+                }
+            }
+        }
+        ctx.set_activity(Activity::playing(format!("{} {}%", playing, perf_percent))).await;
+        sleep(Duration::from_secs(update_frequency)).await;
+    }
+}
+
+// Logging the navs of the fund's price sources drifting apart, and alerting the admin channel once until they agree again.
+async fn check_divergence(ctx: &Context, fund_ticker: &str, readings: &NavReadings, diverged: bool) -> bool {
+    let (threshold, alert_channel) = {
+        let data_read_lock = ctx.data.read().await;
+        let config = data_read_lock.get::<FundConfigContainer>().expect("Expected FundConfig in TypeMap.");
+        (config.divergence_threshold.unwrap_or(DIVERGENCE_THRESHOLD), config.divergence_alert_channel)
+    };
+    if !readings.diverge(threshold) {
+        if diverged {
+            info!("{} price sources agree again: {}", fund_ticker, readings);
+        }
+        return false
+    }
+    if !diverged {
+        let spread = readings.spread().unwrap_or_default().with_precision(2);
+        warn!("{} price sources diverge by {}%: {}", fund_ticker, spread, readings);
+        if let Some(channel) = alert_channel {
+            if let Err(e) = channel.say(&ctx.http, format!("***{} price sources diverge by {}%***\n{}", fund_ticker, spread, readings)).await {
+                info!("{} failed to post the divergence alert\n{}", fund_ticker, e);
+            }
+        }
+    }
+    true
+}
+
+/// Whether the activity shows the time weighted average price performance instead of the spot price one.
+pub async fn twap_activity(ctx: &Context) -> bool {
+    let data_read_lock = ctx.data.read().await;
+    data_read_lock.get::<FundConfigContainer>().expect("Expected FundConfig in TypeMap.").twap_activity
+}
+
+/// Asset class map from the bot config.
+pub async fn asset_classes(ctx: &Context) -> AssetClasses {
+    let data_read_lock = ctx.data.read().await;
+    data_read_lock.get::<FundConfigContainer>().expect("Expected FundConfig in TypeMap.").asset_classes.clone()
+}
+
+/// Allocation in percent at or below which `-stats` sums the assets into one row.
+pub async fn small_asset_threshold(ctx: &Context) -> Decimal {
+    let data_read_lock = ctx.data.read().await;
+    data_read_lock.get::<FundConfigContainer>().expect("Expected FundConfig in TypeMap.").small_asset_threshold()
+}
+
+/// Price source of a fund by name, ticker or alias.
+pub async fn price_source(ctx: &Context, name: &str) -> Option<Arc<dyn PriceSource>> {
+    let data_read_lock = ctx.data.read().await;
+    let registry = data_read_lock.get::<RegistryContainer>().expect("Expected FundRegistry in TypeMap.").read().await;
+    data_read_lock.get::<PriceSourcesContainer>().expect("Expected PriceSources in TypeMap.").source(name, &registry)
+}
+
+/// Price sources of a fund by name, ticker or alias, in failover order.
+pub async fn price_chain(ctx: &Context, name: &str) -> Option<FailoverSource> {
+    let data_read_lock = ctx.data.read().await;
+    let registry = data_read_lock.get::<RegistryContainer>().expect("Expected FundRegistry in TypeMap.").read().await;
+    data_read_lock.get::<PriceSourcesContainer>().expect("Expected PriceSources in TypeMap.").chain(name, &registry)
+}
+
+/// Looks up a fund by name, ticker or alias in the shared fund registry.
+pub async fn lookup_fund(ctx: &Context, name: &str) -> Option<FundId> {
+    let data_read_lock = ctx.data.read().await;
+    let registry = data_read_lock.get::<RegistryContainer>().expect("Expected FundRegistry in TypeMap.");
+    let fund = registry.read().await.lookup(name);
+    fund
+}
+
+/// Shared invictus api client from the bot data.
+pub async fn invictus(ctx: &Context) -> InvictusClient {
+    let data_read_lock = ctx.data.read().await;
+    data_read_lock.get::<InvictusContainer>().expect("Expected InvictusClient in TypeMap.").clone()
+}
+
+/// Shared uniswap subgraph client from the bot data.
+pub async fn uniswap(ctx: &Context) -> UniswapV2Client {
+    let data_read_lock = ctx.data.read().await;
+    data_read_lock.get::<UniswapContainer>().expect("Expected UniswapV2Client in TypeMap.").clone()
+}
+
+/// Chat reply for a failed invictus api call.
+pub fn api_error_reply(error: &InvictusError) -> String {
+    match error {
+        InvictusError::UnknownFund(fund) => format!("Unknown fund *{}*", fund),
+        InvictusError::Http(_) | InvictusError::HttpStatus(_) => "The Invictus api is not reachable at the moment, please try again later".into(),
+        InvictusError::ApiStatus(status) => format!("The Invictus api couldn't answer the request (*{}*)", status),
+        InvictusError::Decode { .. } | InvictusError::MissingField(_) => "Received unexpected data from the Invictus api".into(),
+        InvictusError::RangeNotServed(_) => error.to_string(),
+        InvictusError::Aliases(_) => "Fund aliases are misconfigured".into(),
+    }
+}
+
+/// Refreshes the fund registry from the invictus api every `refresh_frequency` seconds, so newly launched funds are picked up without a restart.
+pub async fn refresh_fund_registry(invictus_client: InvictusClient, registry: Arc<RwLock<FundRegistry>>, aliases: FundAliases, refresh_frequency: u64) {
+    loop {
+        match invictus_client.fund_registry(&aliases).await {
+            Ok(new_registry) if !new_registry.is_empty() => {
+                debug!("fund registry refreshed, {} funds", new_registry.len());
+                *registry.write().await = new_registry;
+            },
+            Ok(_) => info!("fund registry refresh returned no funds, keeping the previous registry"),
+            Err(e) => info!("fund registry refresh failed\n{}", e),
+        }
+        sleep(Duration::from_secs(refresh_frequency)).await;
+    }
+}
@@ -0,0 +1,5 @@
+//! Bot config sections and discord helpers shared by the `base`, `c10` and `icap` bots.
+
+mod config;
+pub use config::{ApiConfig, FundConfig, DEFAULT_ANCHORS_FILE, DEFAULT_REGISTRY_REFRESH};
+pub mod fund_bot;
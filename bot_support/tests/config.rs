@@ -0,0 +1,82 @@
+use bot_support::{ApiConfig, FundConfig};
+use invictus_api::Decimal;
+use serde_derive::Deserialize;
+use serenity::model::id::ChannelId;
+
+// Bot config of a fund nav bot, the way base and c10 flatten the shared sections.
+#[derive(Debug, Deserialize)]
+struct Config {
+    fund_ticker: String,
+    #[serde(flatten)]
+    api: ApiConfig,
+    #[serde(flatten)]
+    fund: FundConfig,
+}
+
+#[test]
+fn example_config_parses() {
+    let example = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/../example_botconfig.toml")).unwrap();
+    let config: Config = toml::from_str(&example).unwrap();
+    assert_eq!(config.fund_ticker, "C20");
+    assert!(config.fund.price_sources.contains_key("icap"));
+    assert_eq!(config.fund.registry_refresh(), bot_support::DEFAULT_REGISTRY_REFRESH);
+    assert!(config.api.invictus_api_url.is_none());
+    assert_eq!(config.fund.small_asset_threshold(), invictus_api::SMALL_ASSET_THRESHOLD);
+}
+
+#[test]
+fn flattened_sections_read_every_setting() {
+    let config: Config = toml::from_str(r#"
+        fund_ticker = "C10"
+        api_timeout = 10
+        uniswap_liquidity_floor = 1000
+        registry_refresh = 600
+        twap_activity = true
+        divergence_threshold = 5
+        divergence_alert_channel = 831545825753694229
+        small_asset_threshold = 0.5
+
+        [asset_classes]
+        Cash = ["USD", "USDC"]
+        Gold = ["PAXG"]
+
+        [api_cache]
+        funds = 0
+        pie = 60
+        nav = 30
+        movement = 300
+
+        [request_policy.rate_limits."api.etherscan.io"]
+        requests_per_sec = 5
+        burst = 5
+    "#).unwrap();
+    assert_eq!(config.api.api_timeout, Some(10));
+    assert_eq!(config.api.uniswap_liquidity_floor, Some(1000.0));
+    assert_eq!(config.api.api_cache.funds, 0);
+    assert_eq!(config.api.request_policy.rate_limits["api.etherscan.io"].requests_per_sec, 5.0);
+    assert_eq!(config.fund.registry_refresh(), 600);
+    assert!(config.fund.twap_activity);
+    assert_eq!(config.fund.divergence_threshold, Some(Decimal::new(5, 0)));
+    assert_eq!(config.fund.divergence_alert_channel, Some(ChannelId(831545825753694229)));
+    assert_eq!(config.fund.asset_classes.class_of("PAXG"), "Gold");
+    assert_eq!(config.fund.small_asset_threshold(), Decimal::new(5, 1));
+}
+
+#[test]
+fn zero_request_rate_is_rejected() {
+    let error = toml::from_str::<Config>(r#"
+        fund_ticker = "C10"
+
+        [request_policy.rate_limits."api.etherscan.io"]
+        requests_per_sec = 0
+        burst = 5
+    "#).unwrap_err();
+    assert!(error.to_string().contains("requests_per_sec must be at least"), "{}", error);
+}
+
+#[test]
+fn request_policy_used_before_init_is_reported() {
+    let config: Config = toml::from_str(r#"fund_ticker = "C10""#).unwrap();
+    api_policy::RequestPolicy::global();
+    assert!(!config.api.init_request_policy());
+}
@@ -1,167 +1,257 @@
+use std::convert::TryFrom;
+
 use serenity::framework::standard::{macros::command, Args, CommandResult};
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 
 use thousands::Separable;
-use tracing::{error, info, debug};
 
 use invictus_api::*;
-use uniswap_v2_api as uniswap;
-use etherscan_io_api as etherscan;
+use api_time::{TimeRange, TimeRangeError, DEFAULT_RANGES};
+use etherscan_io_api::{EtherscanError, TokenFlows};
 use crate::utils;
 // use etherscan_io_api::{get_block_by_timestamp, get_last_block_num, eth_price, Epoch};
 
 #[command]
 pub async fn nav(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-
-    let mut fund_to_check = String::new();
-    let api_response = api_general().await?;
-    let funds_general_raw = api_response.data;
-    let mut fund_found = false;
-
-    if args.len() == 0 {
-        fund_to_check  = "crypto10".to_string();
-    } else if args.len() == 1 {
-        let fund_name = args.single::<String>()?;
-        if fund_name.to_lowercase() == "icap" {
-            let mut nav = uniswap::fund_nav(&fund_name).await.unwrap_or("failed".into());
-            nav.truncate(nav.find(".").unwrap_or(1) + 4);
-            msg.channel_id.say(&ctx.http, format!("***{} NAV:***\n**{}$**", fund_name.to_uppercase(), nav)).await?;
-            fund_found = true;
-        } else {
-            match normalize_fund_name(&fund_name) {
-                Ok(checked_name) => {
-                    fund_to_check = checked_name;
-                }
-                Err(_) => {
-                    msg.reply(&ctx.http, "Unknown fund").await?;
-                    return Ok(());
-                }
-            };
-            for fund in funds_general_raw {
-                if fund.name == fund_to_check {
-                    let mut nav = fund.nav_per_token;
-                    nav.truncate(nav.find(".").unwrap_or(1) + 4);
-                    msg.channel_id.say(&ctx.http, format!("***{} NAV:***\n**{}$**", fund_name.to_uppercase(), nav)).await?;
-                    fund_found = true;
-                }
-            }
-        }
-    }
-
-    if !fund_found {
-        msg.channel_id.say(&ctx.http, format!("Cannot find fund in received data")).await?;
+    if args.len() > 1 {
+        msg.reply(&ctx.http, "Too many arguments, please check `-help`").await?;
+        return Ok(())
     }
+    let fund_name = if args.is_empty() { "c10".to_string() } else { args.single::<String>()? };
+    let source = match utils::price_source(ctx, &fund_name).await {
+        Some(source) => source,
+        None => {
+            msg.reply(&ctx.http, "Unknown fund").await?;
+            return Ok(())
+        }
+    };
+    let nav = source.nav().await?.with_precision(3);
+    msg.channel_id.say(&ctx.http, format!("***{} NAV:***\n**{}$**", fund_name.to_uppercase(), nav)).await?;
     Ok(())
 }
 
 #[allow(unused_assignments)]
 #[command]
 pub async fn stats(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let mut fund_to_check = String::new();
-    if args.len() == 0 {
-        fund_to_check  = "crypto10".to_string();
-    } else if args.len() == 1 {
+    let mut fund_to_check = FundId::new("crypto10");
+    let mut list_all = false;
+    if args.len() > 2 {
+        msg.reply(&ctx.http, "Too many arguments, please check `-help`").await?;
+        return Ok(())
+    }
+    if !args.is_empty() {
         let arg = args.single::<String>()?;
-        match normalize_fund_name(&arg) {
-            Ok(checked_name) => {
-                fund_to_check = checked_name;
+        match utils::lookup_fund(ctx, &arg).await {
+            Some(checked_fund) => {
+                fund_to_check = checked_fund;
             }
-            Err(_) => {
+            None => {
                 msg.reply(&ctx.http, "Unknown fund").await?;
                 return Ok(())
             }
         };
-    } else {
-        msg.reply(&ctx.http, "Too many arguments, please check `-help`").await?;
-        return Ok(())
+    }
+    if !args.is_empty() {
+        if args.single::<String>()?.to_lowercase() != "all" {
+            msg.reply(&ctx.http, "Unknown option, please check `-help`").await?;
+            return Ok(())
+        }
+        list_all = true;
     }
     
-    let mut api_response = FundPie::fund_pie(&fund_to_check).await?;
-    api_response.stablecoin_summary();
-    api_response.remove_small_assets();
-    let fund_nav = FundNav::fund_nav(&fund_to_check).await?;
-    let fund_net_value = (fund_nav.net_asset_value().parse::<f64>().unwrap()) as i64;
+    let invictus_client = utils::invictus(ctx).await;
+    let mut api_response = invictus_client.fund_pie(&fund_to_check).await?;
+    let asset_classes = utils::asset_classes(ctx).await;
+    let class_totals = api_response.grouped_by_class(&asset_classes);
+    let threshold = if list_all { Decimal::ZERO } else { utils::small_asset_threshold(ctx).await };
+    api_response.remove_small_assets(&asset_classes, threshold);
+    let fund_nav = invictus_client.fund_nav(&fund_to_check).await?;
+    let fund_net_value = fund_nav.net_asset_value().whole();
 
-    let mut summary = String::from(format!("*{}*\n", fund_to_check.to_uppercase()));
+    let mut summary = format!("*{}*\n", fund_to_check.as_str().to_uppercase());
     summary.push_str(&format!("**Fund Net Value**: ${}\n", fund_net_value.separate_with_commas()));
     for asset in api_response.assets {
-        let asset_usd = (asset.value.parse::<f64>().unwrap()) as i64;
+        let asset_usd = asset.value.whole();
         summary.push_str(&format!("**{} {}%** ${}\n", asset.ticker, asset.percentage, asset_usd.separate_with_commas()));
     }
-    msg.channel_id.say(&ctx.http, format!("{}", summary)).await?;
+    summary.push_str("*Asset classes*\n");
+    for class_total in class_totals {
+        summary.push_str(&format!("**{} {}%** ${}\n", class_total.class, class_total.percentage.with_precision(2), class_total.value.whole().separate_with_commas()));
+    }
+    msg.channel_id.say(&ctx.http, summary).await?;
     Ok(())
 }
 
-#[allow(unused_assignments)]
 #[command]
 pub async fn perf(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    
-    let default_ranges = vec!["1h", "12h", "24h", "1w", "4w", "52w"];
-    let mut fund_name = String::new();
-    // let mut range = String::new();
-    let mut return_message = String::new();
-    if args.len() == 0 {
-        fund_name  = "c10".to_string();
-        for range in default_ranges {
-            let api_response = fund_perf("crypto10", range).await?;
-            return_message.push_str(&format!("**{} {}%**\n", range, api_response))
-        }
-    } else if args.len() == 1 {
+    if args.len() > 2 {
+        msg.reply(&ctx.http, "Too many arguments, please check `-help`").await?;
+        return Ok(())
+    }
+    let mut fund_name = "c10".to_string();
+    let mut ranges = DEFAULT_RANGES.to_vec();
+    let fund_only = args.len() == 1;
+    if args.len() == 2 {
         fund_name = args.single::<String>()?;
-        // println!("fund name {}", fund_name);
-        if fund_name.to_lowercase() == "icap" {
-            for range in default_ranges {
-                let api_response = uniswap::fund_perf(uniswap::ICAP, range).await.unwrap();
-                return_message.push_str(&format!("**{} {}%**\n", range, api_response))
-            }
-        } else {
-            match normalize_fund_name(&fund_name) {
-                Ok(checked_name) => {
-                    // fund_name = checked_name;
-                    for range in default_ranges {
-                        let api_response = fund_perf(&checked_name, range).await?;
-                        return_message.push_str(&format!("**{} {}%**\n", range, api_response))
-                    }
-                }
-                Err(_) => {
-                    msg.reply(&ctx.http, "Unknown fund").await?;
-                    return Ok(())
-                }
+    }
+    if !args.is_empty() {
+        let arg = args.single::<String>()?;
+        match arg.parse::<TimeRange>() {
+            Ok(range) => ranges = vec![range],
+            // a single argument that doesn't look like a range is the fund
+            Err(TimeRangeError::Invalid(_)) if fund_only => fund_name = arg,
+            Err(e) => {
+                msg.reply(&ctx.http, e.to_string()).await?;
+                return Ok(())
             }
         }
-    } else if args.len() == 2 {
-        fund_name = args.single::<String>()?;
-        let range = args.single::<String>()?;
-        let mut api_response = String::new();
-        // if range.to_lowercase().ends_with("w") {
-        //     let num_weeks = range.to_lowercase().replace("w", "").parse::<i64>()?;
-        //     if num_weeks > 26 {
-        //         msg.reply(&ctx.http, "Max 26w").await?;
-        //         return Ok(())
-        //     }
-        // }
-
-        if fund_name.to_lowercase() == "icap" {
-            api_response = uniswap::fund_perf(uniswap::ICAP, &range).await.unwrap();
-        } else {
-            match normalize_fund_name(&fund_name) {
-                Ok(checked_name) => {
-                    api_response = fund_perf(&checked_name, &range).await?;
-                },
-                Err(_) => {
-                    msg.reply(&ctx.http, "Unknown fund").await?;
-                    return Ok(())
-                }
-            };
+    }
+
+    let source = match utils::price_source(ctx, &fund_name).await {
+        Some(source) => source,
+        None => {
+            msg.reply(&ctx.http, "Unknown fund").await?;
+            return Ok(())
+        }
+    };
+    let percents = match source.perf_ranges(&ranges).await {
+        Ok(percents) => percents,
+        Err(e) => match e.downcast_ref::<InvictusError>() {
+            Some(error @ InvictusError::RangeNotServed(_)) => {
+                msg.reply(&ctx.http, utils::api_error_reply(error)).await?;
+                return Ok(())
+            }
+            _ => return Err(e.into()),
         }
-        return_message.push_str(&format!("**{} {}%**\n", range, api_response))
+    };
+    let mut return_message = String::new();
+    for (range, percent) in ranges.iter().zip(percents) {
+        return_message.push_str(&format!("**{} {}%**\n", range.label(), percent.with_precision(2)))
     }
     
-    msg.channel_id.say(&ctx.http, format!("***{} performance***\n{}", fund_name.to_uppercase(), return_message.replace("24h", "1d").replace("4w", "1m").replace("52w", "1y"))).await?;
+    msg.channel_id.say(&ctx.http, format!("***{} performance***\n{}", fund_name.to_uppercase(), return_message)).await?;
+    Ok(())
+}
+
+#[command]
+pub async fn liquidity(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    if args.is_empty() || args.len() > 2 {
+        msg.reply(&ctx.http, "Command usage\n-liquidity <token> [timerange]\neg:\n`-liquidity icap` `-liquidity icap 1w`").await?;
+        return Ok(())
+    }
+    let token_name = args.single::<String>()?;
+    let token = match utils::fund_token(ctx, &token_name).await {
+        Some(token) => token.address.to_lowercase(),
+        None if token_name.starts_with("0x") && token_name.len() == 42 => token_name.to_lowercase(),
+        None => {
+            msg.reply(&ctx.http, "Unknown token, use a fund of `fund_tokens` in the bot config or a token address").await?;
+            return Ok(())
+        }
+    };
+    let range = if args.is_empty() {
+        TimeRange::Days(1)
+    } else {
+        match args.single::<String>()?.parse::<TimeRange>() {
+            Ok(range) => range,
+            Err(e) => {
+                msg.reply(&ctx.http, e.to_string()).await?;
+                return Ok(())
+            }
+        }
+    };
+
+    let stats = utils::uniswap(ctx).await.liquidity(&token, range).await?;
+    let mut summary = format!("***{} uniswap liquidity, past {}***\n", token_name.to_uppercase(), range.label());
+    summary.push_str(&format!("**Liquidity** ${} ({})\n", usd(stats.reserve_usd()), usd_change(stats.reserve_usd_delta())));
+    summary.push_str(&format!("**Volume** ${}\n", usd(stats.volume_usd_delta())));
+    summary.push_str(&format!("**Trades** {}\n", stats.tx_count_delta().separate_with_commas()));
+    for pair in stats.pairs {
+        summary.push_str(&format!("**{}** ${} ({}) volume ${}\n", pair.name, usd(pair.reserve_usd), usd_change(pair.reserve_usd_delta), usd(pair.volume_usd_delta)));
+    }
+    msg.channel_id.say(&ctx.http, summary).await?;
+    Ok(())
+}
+
+#[command]
+pub async fn flows(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    if args.len() != 2 {
+        msg.reply(&ctx.http, "Command usage\n-flows <ticker> <timerange>\neg:\n`-flows icap 1w` `-flows c20 ytd`").await?;
+        return Ok(())
+    }
+    let fund_name = args.single::<String>()?;
+    let range = match args.single::<String>()?.parse::<TimeRange>() {
+        Ok(range) => range,
+        Err(e) => {
+            msg.reply(&ctx.http, e.to_string()).await?;
+            return Ok(())
+        }
+    };
+    let token = match utils::fund_token(ctx, &fund_name).await {
+        Some(token) => token,
+        None => {
+            msg.reply(&ctx.http, "No token address for the fund, see `fund_tokens` in the bot config").await?;
+            return Ok(())
+        }
+    };
+    let source = match utils::price_source(ctx, &fund_name).await {
+        Some(source) => source,
+        None => {
+            msg.reply(&ctx.http, "Unknown fund").await?;
+            return Ok(())
+        }
+    };
+
+    // the uniswap client's estimator, so the range start block is shared with the perf lookups
+    let blocks = utils::uniswap(ctx).await.block_estimator().clone();
+    let range_start = match range.start_at(blocks.clock().now()) {
+        Some(range_start) => range_start,
+        None => {
+            msg.reply(&ctx.http, "The time range starts before the unix epoch").await?;
+            return Ok(())
+        }
+    };
+    let from_block = blocks.block_at(range_start).await?;
+    let transfers = match blocks.etherscan().transfers(&token.address, from_block, None).await {
+        Ok(transfers) => transfers,
+        Err(e @ EtherscanError::LogLimit(_)) => {
+            msg.reply(&ctx.http, format!("Can't count the flows, {}", e)).await?;
+            return Ok(())
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let flows = TokenFlows::from_transfers(&transfers);
+    let nav = source.nav().await?;
+
+    let tokens = |raw: i128| Decimal::try_from_i128_with_scale(raw, token.decimals);
+    let issued = tokens(i128::try_from(flows.minted)?)?;
+    let redeemed = tokens(i128::try_from(flows.burned)?)?;
+    let net = tokens(flows.net())?;
+    let sign = if net.is_sign_negative() { "-" } else { "+" };
+    let mut summary = format!("***{} flows, past {}***\n", fund_name.to_uppercase(), range.label());
+    summary.push_str(&format!("**Issued** {} tokens (${}) in {} mints\n", whole(issued), whole(issued * nav), flows.mints));
+    summary.push_str(&format!("**Redeemed** {} tokens (${}) in {} burns\n", whole(redeemed), whole(redeemed * nav), flows.burns));
+    summary.push_str(&format!("**Net** {}{} tokens ({}${})\n", sign, whole(net.abs()), sign, whole(net.abs() * nav)));
+    summary.push_str(&format!("At the current NAV of {}$", nav.with_precision(3)));
+    msg.channel_id.say(&ctx.http, summary).await?;
     Ok(())
 }
 
+// Whole units with thousands separators.
+fn whole(value: Decimal) -> String {
+    value.whole().separate_with_commas()
+}
+
+// Whole dollars with thousands separators.
+fn usd(value: f64) -> String {
+    (value.round() as i64).separate_with_commas()
+}
+
+fn usd_change(value: f64) -> String {
+    let sign = if value < 0.0 { "-" } else { "+" };
+    format!("{}${}", sign, usd(value.abs()))
+}
+
 // #[command]
 // pub async fn stake(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
 //     if !in_allowed_channels(&msg.channel_id.0 ) {
@@ -173,7 +263,7 @@ pub async fn perf(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
 //         return Ok(())
 //     }
 
-//     msg.channel_id.say(&ctx.http, format!("** %** *()*\n",)).await?;
+//     msg.channel_id.say(&ctx.http, "** %** *()*\n").await?;
 //     Ok(())
 
 // }
@@ -181,9 +271,9 @@ pub async fn perf(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
 #[command]
 pub async fn info(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
 
-    let fund_nav = FundNav::fund_nav("c10").await?;
-    let net_value_raw: i64 = (fund_nav.net_asset_value().parse::<f64>().unwrap()) as i64;
-    let net_value = String::from(format!("**Fund Net Value**: ${}\n", net_value_raw.separate_with_commas()));
+    let fund_nav = utils::invictus(ctx).await.fund_nav(&FundId::new("crypto10")).await?;
+    let net_value_raw: i64 = fund_nav.net_asset_value().whole();
+    let net_value = format!("**Fund Net Value**: ${}\n", net_value_raw.separate_with_commas());
     let _ = msg.channel_id.send_message(&ctx.http, |m| {
         m.embed(|e| {
             e.title(net_value);
@@ -207,9 +297,10 @@ pub async fn help(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
     perf_help.push_str("`-perf <timerange>` returns the C10 fund preformance summary for the given timerange.\n");
     perf_help.push_str("`-perf <ticker>` returns the given fund preformance summary for the past 1 year.\n");
     perf_help.push_str("`-perf <ticker> <timerange>` returns the given fund performance summary for the given timerange.\n");
+    perf_help.push_str("Time ranges: `<n>h` up to 48h, `<n>d` up to 31d, `<n>w` up to 52w, `<n>m` up to 12m, `<n>y` up to 3y and `ytd`, Invictus api funds read days up to 2d or in whole weeks.\n");
     perf_help.push_str("(ex):\n`-perf c20` for C20 fund performance summary of past 1 year\n");
     perf_help.push_str("`-perf c20 4w` for C20 fund performance summary of past 4 weeks \n");
-    perf_help.push_str("`-perf iml 52w` for IML fund performance summary of past 52 weeks / 1 year \n");
+    perf_help.push_str("`-perf iml 1y` for IML fund performance summary of past 1 year \n");
     let _ = msg.channel_id.send_message(&ctx.http, |m| {
         m.embed(|e| {
             e.title("C10 bot help");
@@ -218,8 +309,10 @@ pub async fn help(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
             e.field("-help", "This help message.", false);
             e.field("-info", "Useful links.", false);
             e.field("-nav", "Current token value. \neg.: `-nav` `-nav <ticker>`", false);
-            e.field("-stats", "Current fund asset allocation statistics. \neg.:`-stats` `-stats <ticker>`", false);
+            e.field("-stats", "Current fund asset allocation statistics. \nAssets at or below the threshold are summed into one row, `all` lists every asset.\neg.:`-stats` `-stats <ticker>` `-stats <ticker> all`", false);
             e.field("-perf", perf_help, false);
+            e.field("-flows", "Tokens issued and redeemed over a timerange, in tokens and in USD at the current NAV. \neg.: `-flows icap 1w` `-flows <ticker> <timerange>`", false);
+            e.field("-liquidity", "Uniswap liquidity, volume and trades of a token's pairs over a timerange, the past 1 day by default. \neg.: `-liquidity icap` `-liquidity icap 1w` `-liquidity <token address>`", false);
             e
         });
         m
@@ -248,14 +341,14 @@ pub async fn lrb(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
 // }
 
 #[command]
-pub async fn stake(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+pub async fn stake(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
    
     if args.len() != 3 {
         msg.reply_ping(&ctx.http, "I need 3 arguments for the calculation: <amount> <token> <length>").await?;
         return Ok(())
     }
 
-    msg.channel_id.say(&ctx.http, format!("** %** *()*\n",)).await?;
+    msg.channel_id.say(&ctx.http, "** %** *()*\n").await?;
     Ok(())
 
 }
\ No newline at end of file
@@ -2,46 +2,19 @@ use serde_derive::Deserialize;
 use anyhow::Result;
 use serenity::{
     http::Http,
-    model::id::GuildId,
     model::id::ChannelId,
-    model::gateway::Activity,
     prelude::*
     };
-use std::{
-    sync::{Arc},
-};
+use std::collections::HashMap;
 
+use api_time::TimeRange;
 use tracing::{info, debug};
 use tokio::time::{sleep, Duration};
 use thousands::Separable;
 use invictus_api::*;
-
-pub async fn update_nick_and_activity(ctx: Arc<Context>, fund_ticker: &str, guilds: Vec<GuildId>, update_frequency: u64, playing: &str) {
-    let fund_name = normalize_fund_name(fund_ticker).expect("update_nick, Fund name unknown");
-    loop {
-        let perf_percent = match fund_perf(&fund_ticker, playing).await {
-            Ok(percent) => percent,
-            Err(e) => {
-                info!("fund_perf {} api call failed\n{}", &fund_ticker, e.to_string());
-                sleep(Duration::from_secs(update_frequency)).await;
-                continue;
-            },
-        };
-        let trend = if perf_percent.contains("-") {"⬂"} else {"⬀"};
-            
-        let mut fund_nav = fund_nav(&fund_name).await.unwrap_or("failed".into());
-        fund_nav.truncate(fund_nav.find(".").unwrap_or(1) + 4);
-        for server in guilds.clone() {
-            if let Err(e) = server.edit_nickname(&ctx.http, Some(&format!("{} ${} {}",fund_ticker, fund_nav, trend))).await {
-                info!("{} failed to update nick with nav\n{}", &fund_ticker, e);
-                sleep(Duration::from_secs(update_frequency)).await;
-                continue;
-            }
-        }
-        ctx.set_activity(Activity::playing(format!("{} {}%", playing, perf_percent))).await;
-        sleep(Duration::from_secs(update_frequency)).await;
-    }
-}
+use bot_support::{ApiConfig, FundConfig};
+pub use bot_support::fund_bot::*;
+use uniswap_v2_api as uniswap;
 
 impl TypeMapKey for Config {
     type Value = Config;
@@ -51,9 +24,45 @@ pub struct Config {
     pub fund_ticker: String,
     pub bot_token: String,
     pub update_frequency: u64,
-    pub playing: String,
+    pub playing: TimeRange,
     pub prefix: String,
-    pub allowed_channels: Vec<ChannelId>
+    pub allowed_channels: Vec<ChannelId>,
+    #[serde(flatten)]
+    pub api: ApiConfig,
+    #[serde(flatten)]
+    pub fund: FundConfig,
+    pub whale_alerts: Option<WhaleAlerts>,
+    #[serde(default)]
+    pub fund_tokens: HashMap<String, FundToken>,
+}
+
+// Token contract of a fund, for the `-flows` mints and burns.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FundToken {
+    pub address: String,
+    #[serde(default = "default_token_decimals")]
+    pub decimals: u32,
+}
+
+fn default_token_decimals() -> u32 {
+    18
+}
+
+// Large swap alert settings, the alerts are off without this table in the config.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WhaleAlerts {
+    pub token: String,
+    pub threshold_usd: f64,
+    pub channels: Vec<ChannelId>,
+    pub poll_interval: Option<u64>,
+    pub state_file: Option<String>,
+}
+
+impl WhaleAlerts {
+    // Swap cursor file, relative to the working directory.
+    pub fn state_file(&self) -> &str {
+        self.state_file.as_deref().unwrap_or("whale_swaps.toml")
+    }
 }
 
 // Loading bot config file.
@@ -63,7 +72,25 @@ pub fn loadconfig() -> Result<Config> {
     Ok(config)
 }
 
-pub async fn c10_rebalance_check(http: &Http) {
+// Token contract of a fund from the fund_tokens config, keyed by the name itself or by any name, ticker or alias of the same fund.
+pub async fn fund_token(ctx: &Context, name: &str) -> Option<FundToken> {
+    let data_read_lock = ctx.data.read().await;
+    let config = data_read_lock.get::<Config>().expect("Expected Config in TypeMap.");
+    if let Some((_, token)) = config.fund_tokens.iter().find(|(key, _)| key.eq_ignore_ascii_case(name.trim())) {
+        return Some(token.clone())
+    }
+    let registry = data_read_lock.get::<RegistryContainer>().expect("Expected FundRegistry in TypeMap.").read().await;
+    let fund = registry.lookup(name)?;
+    config.fund_tokens.iter()
+        .filter(|(key, _)| registry.lookup(key).as_ref() == Some(&fund))
+        .min_by_key(|(key, _)| key.as_str())
+        .map(|(_, token)| token.clone())
+}
+
+// Watching the C10 allocation for rebalances. The whole pie is compared, so assets crossing the small asset threshold
+// aren't taken for a rebalance, the message lists the assets above the threshold.
+pub async fn c10_rebalance_check(http: &Http, invictus_client: &InvictusClient, asset_classes: &AssetClasses, small_asset_threshold: Decimal) {
+    let c10 = FundId::new("crypto10");
 
     let mut previous_asset_values: Vec<FundPieAsset> = vec![];
     let rebalance_channels = vec![
@@ -72,7 +99,7 @@ pub async fn c10_rebalance_check(http: &Http) {
         ChannelId(799268744890679377)]; //  rebalance channel IDs
 
     loop {
-        let mut api_response = match FundPie::fund_pie("c10").await {
+        let mut api_response = match invictus_client.fund_pie(&c10).await {
             Ok(response) => response,
             Err(e) => {
                 info!("c10_rebalance_check failed to retrieve pie data from the api\n{}", e);
@@ -80,7 +107,8 @@ pub async fn c10_rebalance_check(http: &Http) {
                 continue;
             } 
         };
-        api_response.remove_small_assets();
+        api_response.stablecoin_summary(asset_classes);
+        api_response.assets.retain(|asset| !asset.percentage.is_zero());
         debug!("api response {:?}", &api_response);
         let current_asset_values = api_response.assets.clone();
         debug!("current values {:?}", &current_asset_values);
@@ -92,14 +120,15 @@ pub async fn c10_rebalance_check(http: &Http) {
         let mut control = RebalanceControl::new(
             previous_asset_values.clone(),
             current_asset_values, 
-            10.0,
+            Decimal::TEN,
+            small_asset_threshold,
         );
 
-        control.run();
+        control.run(asset_classes);
 
         if control.cash_rebalanced || control.crypto_rebalanced {
-            let net_value = match FundNav::fund_nav("c10").await {
-                Ok(value) => (value.net_asset_value().parse::<f64>().unwrap()) as i64,
+            let net_value = match invictus_client.fund_nav(&c10).await {
+                Ok(value) => value.net_asset_value().whole(),
                 Err(_) => 0,
             };
             let mut rebalance_message = String::new();
@@ -107,10 +136,10 @@ pub async fn c10_rebalance_check(http: &Http) {
             if control.cash_rebalanced {
                 rebalance_message.push_str(&format!("*{}*\n", control.value_moved_to ));
             } else {
-                rebalance_message.push_str(&format!("*Crypto assets rebalanced*\n",));
+                rebalance_message.push_str("*Crypto assets rebalanced*\n");
             }
 
-            rebalance_message.push_str(&format!("{}", control.asset_summary));
+            rebalance_message.push_str(&control.asset_summary);
 
             'rbchannels: for channel in &rebalance_channels {
                 if let Err(why) = channel.say(http,  &rebalance_message).await {
@@ -128,10 +157,95 @@ pub async fn c10_rebalance_check(http: &Http) {
     }
 }
 
+// Announces the swaps after the saved cursor, without one only swaps from now on are announced.
+pub async fn whale_alerts(http: &Http, uniswap_client: &uniswap::UniswapV2Client, settings: &WhaleAlerts, saved_cursor: Option<uniswap::SwapCursor>) {
+    let token = settings.token.to_lowercase();
+    let state_file = settings.state_file();
+    let poll_interval = settings.poll_interval.unwrap_or(60);
+    let mut cursor = saved_cursor.unwrap_or_else(|| uniswap::SwapCursor::starting_at(uniswap_client.clock().now() as i64));
+
+    loop {
+        let pairs: Vec<String> = match uniswap_client.token_pairs(&token, None).await {
+            Ok(data) => data.pairs().map(|pair| pair.id.clone()).collect(),
+            Err(e) => {
+                info!("whale_alerts failed to retrieve the {} pairs\n{}", &token, e);
+                sleep(Duration::from_secs(poll_interval)).await;
+                continue;
+            }
+        };
+        let swaps = match uniswap_client.swaps_after(&pairs, &cursor).await {
+            Ok(swaps) => swaps,
+            Err(e) => {
+                info!("whale_alerts failed to retrieve swaps\n{}", e);
+                sleep(Duration::from_secs(poll_interval)).await;
+                continue;
+            }
+        };
+
+        for swap in &swaps {
+            match swap.amount_usd() {
+                Ok(amount_usd) if amount_usd >= settings.threshold_usd => {
+                    let whale_message = whale_message(&token, swap, amount_usd);
+                    for channel in &settings.channels {
+                        if let Err(why) = channel.say(http, &whale_message).await {
+                            info!("whale_alerts failed to send alert\n{}", why);
+                        }
+                    }
+                },
+                Ok(_) => {},
+                Err(e) => info!("whale_alerts skipped swap {}\n{}", &swap.id, e),
+            }
+            if let Err(e) = cursor.advance(swap) {
+                info!("whale_alerts can't move past swap {}\n{}", &swap.id, e);
+            }
+        }
+        if !swaps.is_empty() {
+            if let Err(e) = save_swap_cursor(state_file, &cursor).await {
+                info!("whale_alerts failed to save the last swap to {}\n{}", state_file, e);
+            }
+        }
+
+        sleep(Duration::from_secs(poll_interval)).await;
+    }
+}
+
+fn whale_message(token: &str, swap: &uniswap::queries::Swap, amount_usd: f64) -> String {
+    let (token_symbol, other_symbol) = if swap.pair.token0.id == token {
+        (&swap.pair.token0.symbol, &swap.pair.token1.symbol)
+    } else {
+        (&swap.pair.token1.symbol, &swap.pair.token0.symbol)
+    };
+    let mut whale_message = format!(":whale:**  {} whale swap  **:whale:\n**${}**\n", token_symbol, (amount_usd.round() as i64).separate_with_commas());
+    if let Ok(token_amount) = swap.token_amount(token) {
+        let side = if token_amount < 0.0 { "Sold" } else { "Bought" };
+        let preposition = if token_amount < 0.0 { "for" } else { "with" };
+        whale_message.push_str(&format!("{} {} {} {} {}\n", side, (token_amount.abs().round() as i64).separate_with_commas(), token_symbol, preposition, other_symbol));
+    }
+    whale_message.push_str(&format!("https://etherscan.io/tx/{}", swap.transaction()));
+    whale_message
+}
+
+// Last processed swap saved to the state file, `None` before the first save.
+// An unreadable or corrupt file is an error, starting from now would skip the swaps since the save.
+pub fn load_swap_cursor(state_file: &str) -> Result<Option<uniswap::SwapCursor>> {
+    let cursor_string = match std::fs::read_to_string(state_file) {
+        Ok(cursor_string) => cursor_string,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(Some(toml::from_str(&cursor_string)?))
+}
+
+async fn save_swap_cursor(state_file: &str, cursor: &uniswap::SwapCursor) -> Result<()> {
+    tokio::fs::write(state_file, toml::to_string(cursor)?).await?;
+    Ok(())
+}
+
 struct RebalanceControl {
     previous_values: Vec<FundPieAsset>,
     current_values: Vec<FundPieAsset>,
-    movement_tolerance: f64,
+    movement_tolerance: Decimal,
+    summary_threshold: Decimal,
     crypto_rebalanced: bool,
     cash_rebalanced: bool,
     value_moved_to: String,
@@ -139,11 +253,12 @@ struct RebalanceControl {
 }
 
 impl RebalanceControl {
-    fn new(previous_values: Vec<FundPieAsset>, current_values: Vec<FundPieAsset>, movement_tolerance: f64) -> Self {
+    fn new(previous_values: Vec<FundPieAsset>, current_values: Vec<FundPieAsset>, movement_tolerance: Decimal, summary_threshold: Decimal) -> Self {
         Self {
             previous_values,
             current_values,
             movement_tolerance,
+            summary_threshold,
             crypto_rebalanced: false,
             cash_rebalanced: false,
             value_moved_to: "".to_string(),
@@ -151,37 +266,44 @@ impl RebalanceControl {
         }
     }
 
-    fn run(&mut self) {
+    fn run(&mut self, asset_classes: &AssetClasses) {
+        // cash is compared as a class, so value moving between stablecoins is not a rebalance
+        let current_cash = asset_classes.percentage_of(&self.current_values, CASH_CLASS);
+        let previous_cash = asset_classes.percentage_of(&self.previous_values, CASH_CLASS);
+        if (current_cash - self.movement_tolerance) > previous_cash {
+            self.cash_rebalanced = true;
+            self.value_moved_to = "Value moved to Cash".into()
+        } else if (current_cash + self.movement_tolerance) < previous_cash {
+            self.cash_rebalanced = true;
+            self.value_moved_to = "Value moved to Cryptocurrencies".into()
+        }
+
         for asset_curr in &self.current_values {
+            let is_cash = asset_classes.is_cash(&asset_curr.ticker);
             let mut asset_found = false;
             for asset_prev in &self.previous_values {
                 if asset_curr.name == asset_prev.name {
                     asset_found = true;
-                    let current_percentage:f64 = asset_curr.percentage.parse().unwrap();
-                    let previous_percentage:f64 = asset_prev.percentage.parse().unwrap();
+                    let current_percentage = asset_curr.percentage;
+                    let previous_percentage = asset_prev.percentage;
                     
-                    if (current_percentage - self.movement_tolerance) > previous_percentage {           // asset allocation increased compared to previous dataset
-                        if asset_curr.ticker == "USD" {
-                            self.cash_rebalanced = true;
-                            self.value_moved_to = "Value moved to Cash".into()
-                        } else {
-                            self.crypto_rebalanced = true;
-                        }
-    
-                    } else if (current_percentage + self.movement_tolerance) < previous_percentage {    // asset allocation decreased compared to previous dataset
-                        if asset_curr.ticker == "USD" {
-                            self.cash_rebalanced = true;
-                            self.value_moved_to = "Value moved to Cryptocurrencies".into()
-                        } else {
-                            self.crypto_rebalanced = true;
-                        }
+                    if !is_cash && (current_percentage - self.movement_tolerance) > previous_percentage {           // asset allocation increased compared to previous dataset
+                        self.crypto_rebalanced = true;
+                    } else if !is_cash && (current_percentage + self.movement_tolerance) < previous_percentage {    // asset allocation decreased compared to previous dataset
+                        self.crypto_rebalanced = true;
                     }
                     debug!("**{} {}%** *was {}%*\n",asset_curr.ticker, current_percentage, previous_percentage);
+                    if current_percentage <= self.summary_threshold && previous_percentage <= self.summary_threshold {
+                        continue;
+                    }
                     self.asset_summary.push_str(&format!("**{} {}%** *(before {}%*)\n",asset_curr.ticker, current_percentage, previous_percentage));
                 }
             }
             if !asset_found { // if we can't find one of the assets in the previous dataset that is part of the fund now, we can assume that a rebalance happened 
-                self.crypto_rebalanced = true;
+                self.crypto_rebalanced |= !is_cash;
+                if asset_curr.percentage <= self.summary_threshold {
+                    continue;
+                }
                 self.asset_summary.push_str(&format!("**{} {}%** *new token in the fund*\n", asset_curr.ticker, asset_curr.percentage ));
             }
         }
@@ -199,6 +321,7 @@ pub fn get_last_rebalance_info(ticker: &str) -> Result<RebalanceInfo> {
 #[derive(Debug, Deserialize)]
 pub struct RebalanceInfo {
     pub date: String,
+    #[allow(dead_code)]
     pub lrb_type: String,
     pub stats: Vec<String>,
 }
\ No newline at end of file
@@ -0,0 +1,40 @@
+// Calldata and return values of the few contract calls the client makes, no abi library needed for them.
+
+/// `getReserves()` of a Uniswap V2 pair.
+pub(crate) const GET_RESERVES: &str = "0x0902f1ac";
+/// `token0()` of a Uniswap V2 pair.
+pub(crate) const TOKEN0: &str = "0x0dfe1681";
+/// `token1()` of a Uniswap V2 pair.
+pub(crate) const TOKEN1: &str = "0xd21220a7";
+/// `decimals()` of an ERC-20 token.
+pub(crate) const DECIMALS: &str = "0x313ce567";
+
+const WORD_HEX: usize = 64;
+
+/// 32 byte words of an `eth_call` return value.
+pub(crate) fn words(data: &str) -> Option<Vec<&str>> {
+    let data = data.strip_prefix("0x")?;
+    if data.is_empty() || data.len() % WORD_HEX != 0 || !data.is_ascii() {
+        return None
+    }
+    Some((0..data.len() / WORD_HEX).map(|index| &data[index * WORD_HEX..(index + 1) * WORD_HEX]).collect())
+}
+
+/// Unsigned word value, `None` past the `u128` range.
+pub(crate) fn word_u128(word: &str) -> Option<u128> {
+    let (high, low) = word.split_at(WORD_HEX - 32);
+    if high.chars().any(|digit| digit != '0') {
+        return None
+    }
+    u128::from_str_radix(low, 16).ok()
+}
+
+/// Address held in the low 20 bytes of a word.
+pub(crate) fn word_address(word: &str) -> String {
+    format!("0x{}", &word[WORD_HEX - 40..]).to_lowercase()
+}
+
+/// Hex quantity of the json-rpc api, eg.: `0x3e8`.
+pub(crate) fn quantity(value: &str) -> Option<u64> {
+    u64::from_str_radix(value.strip_prefix("0x")?, 16).ok()
+}
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use api_policy::RequestPolicy;
+use api_time::TimeRange;
+use serde::de::DeserializeOwned;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::abi::{quantity, word_address, word_u128, words, DECIMALS, GET_RESERVES, TOKEN0, TOKEN1};
+use crate::{Block, BlockId, PairTokens, Reserves, Result, RpcError};
+
+/// Local dev node, eg.: a mainnet fork.
+pub const DEFAULT_NODE_URL: &str = "http://127.0.0.1:8545";
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Serialize)]
+struct Request<'a> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    result: Option<Value>,
+    error: Option<ErrorObject>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorObject {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawBlock {
+    number: String,
+    timestamp: String,
+    hash: String,
+}
+
+/// Ethereum json-rpc client of any node url, reads blocks and Uniswap V2 pairs without an indexer.
+/// Cloning is cheap, clones share the same connection pool and pair token cache.
+#[derive(Debug, Clone)]
+pub struct EthRpcClient {
+    http: reqwest::Client,
+    url: String,
+    policy: Arc<RequestPolicy>,
+    pairs: Arc<Mutex<HashMap<String, PairTokens>>>,
+}
+
+impl EthRpcClient {
+    /// Client of the local node with the default timeout.
+    pub fn new() -> Result<Self> {
+        Self::builder().build()
+    }
+
+    pub fn builder() -> EthRpcClientBuilder {
+        EthRpcClientBuilder::default()
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Number of the chain head, `eth_blockNumber`.
+    pub async fn block_number(&self) -> Result<u64> {
+        const METHOD: &str = "eth_blockNumber";
+        let number: String = self.request(METHOD, json!([])).await?
+            .ok_or(RpcError::InvalidResult { method: METHOD, result: "null".into() })?;
+        quantity(&number).ok_or(RpcError::InvalidResult { method: METHOD, result: number })
+    }
+
+    /// Header of the block, `eth_getBlockByNumber` without the transactions.
+    pub async fn block(&self, block: BlockId) -> Result<Block> {
+        const METHOD: &str = "eth_getBlockByNumber";
+        let raw: RawBlock = match self.request(METHOD, json!([block.param(), false])).await? {
+            Some(raw) => raw,
+            None => return Err(match block {
+                BlockId::Number(number) => RpcError::BlockNotFound(number),
+                BlockId::Latest => RpcError::InvalidResult { method: METHOD, result: "null".into() },
+            }),
+        };
+        let number = quantity(&raw.number).ok_or_else(|| RpcError::InvalidResult { method: METHOD, result: raw.number.clone() })?;
+        let timestamp = quantity(&raw.timestamp).ok_or_else(|| RpcError::InvalidResult { method: METHOD, result: raw.timestamp.clone() })?;
+        Ok(Block { number, timestamp, hash: raw.hash })
+    }
+
+    /// Last block mined at or before the unix timestamp, binary searched between the genesis block and the chain head.
+    pub async fn block_by_timestamp(&self, timestamp: u64) -> Result<Block> {
+        let latest = self.block(BlockId::Latest).await?;
+        if latest.timestamp <= timestamp {
+            return Ok(latest)
+        }
+        let mut found = self.block(BlockId::Number(0)).await?;
+        if found.timestamp > timestamp {
+            return Err(RpcError::NoBlockBefore(timestamp))
+        }
+        // the found block is at or before the timestamp, the high block after it
+        let mut high = latest.number;
+        while high - found.number > 1 {
+            let middle = self.block(BlockId::Number(found.number + (high - found.number) / 2)).await?;
+            if middle.timestamp <= timestamp {
+                found = middle;
+            } else {
+                high = middle.number;
+            }
+        }
+        Ok(found)
+    }
+
+    /// Return data of a read only contract call, `eth_call`.
+    pub async fn call(&self, to: &str, data: &str, block: BlockId) -> Result<String> {
+        const METHOD: &str = "eth_call";
+        self.request(METHOD, json!([{ "to": to, "data": data }, block.param()])).await?
+            .ok_or(RpcError::InvalidResult { method: METHOD, result: "null".into() })
+    }
+
+    /// `getReserves()` of a Uniswap V2 pair.
+    pub async fn reserves(&self, pair: &str, block: BlockId) -> Result<Reserves> {
+        let data = self.call(pair, GET_RESERVES, block).await?;
+        let invalid = || RpcError::InvalidResult { method: "getReserves", result: data.clone() };
+        let words = words(&data).filter(|words| words.len() == 3).ok_or_else(invalid)?;
+        Ok(Reserves {
+            reserve0: word_u128(words[0]).ok_or_else(invalid)?,
+            reserve1: word_u128(words[1]).ok_or_else(invalid)?,
+            block_timestamp_last: word_u128(words[2]).and_then(|timestamp| u32::try_from(timestamp).ok()).ok_or_else(invalid)?,
+        })
+    }
+
+    /// Tokens and decimals of a Uniswap V2 pair, cached after the first lookup.
+    pub async fn pair_tokens(&self, pair: &str) -> Result<PairTokens> {
+        let pair = pair.to_lowercase();
+        if let Some(tokens) = self.pairs.lock().expect("pair cache poisoned").get(&pair) {
+            return Ok(tokens.clone())
+        }
+        let token0 = self.address(&pair, TOKEN0, "token0").await?;
+        let token1 = self.address(&pair, TOKEN1, "token1").await?;
+        let tokens = PairTokens {
+            decimals0: self.decimals(&token0).await?,
+            decimals1: self.decimals(&token1).await?,
+            token0,
+            token1,
+        };
+        self.pairs.lock().expect("pair cache poisoned").insert(pair, tokens.clone());
+        Ok(tokens)
+    }
+
+    /// `decimals()` of an ERC-20 token.
+    pub async fn decimals(&self, token: &str) -> Result<u8> {
+        let data = self.call(token, DECIMALS, BlockId::Latest).await?;
+        words(&data)
+            .and_then(|words| words.first().and_then(|word| word_u128(word)))
+            .and_then(|decimals| u8::try_from(decimals).ok())
+            .ok_or(RpcError::InvalidResult { method: "decimals", result: data })
+    }
+
+    /// Price of `token` in the other token of the Uniswap V2 pair, from the pair reserves at the block.
+    pub async fn pair_price(&self, pair: &str, token: &str, block: BlockId) -> Result<f64> {
+        let tokens = self.pair_tokens(pair).await?;
+        let reserves = self.reserves(pair, block).await?;
+        let reserve0 = reserves.reserve0 as f64 / 10f64.powi(tokens.decimals0.into());
+        let reserve1 = reserves.reserve1 as f64 / 10f64.powi(tokens.decimals1.into());
+        if reserve0 == 0.0 || reserve1 == 0.0 {
+            return Err(RpcError::EmptyReserve(pair.to_lowercase()))
+        }
+        let token = token.to_lowercase();
+        if token == tokens.token0 {
+            Ok(reserve1 / reserve0)
+        } else if token == tokens.token1 {
+            Ok(reserve0 / reserve1)
+        } else {
+            Err(RpcError::TokenNotInPair { pair: pair.to_lowercase(), token })
+        }
+    }
+
+    /// Price change of `token` in the other pair token over the range in percent,
+    /// the range ends at the chain head timestamp.
+    pub async fn pair_perf(&self, pair: &str, token: &str, range: TimeRange) -> Result<f64> {
+        let latest = self.block(BlockId::Latest).await?;
+        let range_start = self.block_by_timestamp(range.start_at(latest.timestamp).ok_or(RpcError::BeforeEpoch(range))?).await?;
+        let price_now = self.pair_price(pair, token, BlockId::Number(latest.number)).await?;
+        let price_previous = self.pair_price(pair, token, BlockId::Number(range_start.number)).await?;
+        Ok((price_now / price_previous - 1.0) * 100.0)
+    }
+
+    async fn address(&self, contract: &str, data: &str, method: &'static str) -> Result<String> {
+        let result = self.call(contract, data, BlockId::Latest).await?;
+        match words(&result).as_deref() {
+            Some([word]) => Ok(word_address(word)),
+            _ => Err(RpcError::InvalidResult { method, result }),
+        }
+    }
+
+    // `None` for a null result, eg.: an unknown block.
+    async fn request<T: DeserializeOwned>(&self, method: &'static str, params: Value) -> Result<Option<T>> {
+        let request = self.http
+            .post(&self.url)
+            .json(&Request { jsonrpc: "2.0", id: 1, method, params })
+            .build()?;
+        let response = self.policy.execute(&self.http, request).await?;
+        let http_status = response.status();
+        let body = response.bytes().await?;
+
+        let response: Response = match serde_json::from_slice(&body) {
+            Ok(response) => response,
+            Err(_) if !http_status.is_success() => return Err(RpcError::HttpStatus(http_status.as_u16())),
+            Err(source) => return Err(RpcError::Decode { method, source }),
+        };
+        if let Some(error) = response.error {
+            return Err(RpcError::Rpc { method, code: error.code, message: error.message })
+        }
+        match response.result {
+            None | Some(Value::Null) => Ok(None),
+            Some(result) => serde_json::from_value(result).map(Some).map_err(|source| RpcError::Decode { method, source }),
+        }
+    }
+}
+
+/// Builder for [`EthRpcClient`], every option falls back to a sane default.
+#[derive(Debug, Clone)]
+pub struct EthRpcClientBuilder {
+    url: String,
+    timeout: Duration,
+    policy: Option<Arc<RequestPolicy>>,
+}
+
+impl Default for EthRpcClientBuilder {
+    fn default() -> Self {
+        Self {
+            url: DEFAULT_NODE_URL.into(),
+            timeout: DEFAULT_TIMEOUT,
+            policy: None,
+        }
+    }
+}
+
+impl EthRpcClientBuilder {
+    /// Node url, eg.: a hosted mainnet endpoint or a local dev node.
+    pub fn url(mut self, url: &str) -> Self {
+        self.url = url.into();
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Retry and rate limit policy, the process wide [`RequestPolicy::global`] if not set.
+    pub fn request_policy(mut self, policy: Arc<RequestPolicy>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    pub fn build(self) -> Result<EthRpcClient> {
+        let http = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .connect_timeout(self.timeout)
+            .build()?;
+        Ok(EthRpcClient {
+            http,
+            url: self.url,
+            policy: self.policy.unwrap_or_else(RequestPolicy::global),
+            pairs: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+}
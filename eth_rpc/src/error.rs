@@ -0,0 +1,41 @@
+use api_time::TimeRange;
+use thiserror::Error;
+
+pub type Result<T, E = RpcError> = std::result::Result<T, E>;
+
+#[derive(Debug, Error)]
+pub enum RpcError {
+    /// Connection, timeout or other transport level failure.
+    #[error("json-rpc request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// Non success http status without a parsable json-rpc response.
+    #[error("node responded with http status {0}")]
+    HttpStatus(u16),
+    /// The response body isn't a json-rpc response with the expected result.
+    #[error("failed to decode the {method} response: {source}")]
+    Decode {
+        method: &'static str,
+        source: serde_json::Error,
+    },
+    /// The node answered with a json-rpc `error` object.
+    #[error("{method} failed with code {code}: {message}")]
+    Rpc { method: &'static str, code: i64, message: String },
+    /// The result doesn't hold the expected value, eg.: a quantity that isn't hex or a short `eth_call` return.
+    #[error("unexpected {method} result: {result}")]
+    InvalidResult { method: &'static str, result: String },
+    /// The node doesn't know the block, eg.: a number past the chain head.
+    #[error("block {0} not found")]
+    BlockNotFound(u64),
+    /// The timestamp is before the first block of the chain.
+    #[error("no block before timestamp {0}")]
+    NoBlockBefore(u64),
+    /// The range ending at the chain head starts before the unix epoch.
+    #[error("the past {} starts before the unix epoch", .0.label())]
+    BeforeEpoch(TimeRange),
+    /// The token is neither token0 nor token1 of the pair.
+    #[error("token {token} is not in pair {pair}")]
+    TokenNotInPair { pair: String, token: String },
+    /// The pair has no reserve of one of its tokens.
+    #[error("pair {0} has an empty reserve")]
+    EmptyReserve(String),
+}
@@ -0,0 +1,55 @@
+mod error;
+pub use error::{Result, RpcError};
+mod abi;
+mod client;
+pub use client::{EthRpcClient, EthRpcClientBuilder, DEFAULT_NODE_URL, DEFAULT_TIMEOUT};
+
+/// Block a call reads the chain state at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockId {
+    Latest,
+    Number(u64),
+}
+
+impl BlockId {
+    // Block parameter of the json-rpc methods, a tag or a hex number.
+    pub(crate) fn param(&self) -> String {
+        match self {
+            BlockId::Latest => "latest".into(),
+            BlockId::Number(number) => format!("{:#x}", number),
+        }
+    }
+}
+
+impl From<u64> for BlockId {
+    fn from(number: u64) -> Self {
+        BlockId::Number(number)
+    }
+}
+
+/// Header fields of a mined block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub number: u64,
+    /// Unix timestamp in seconds.
+    pub timestamp: u64,
+    pub hash: String,
+}
+
+/// Reserves of a Uniswap V2 pair in the smallest unit of each token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reserves {
+    pub reserve0: u128,
+    pub reserve1: u128,
+    /// Timestamp of the block the reserves last changed in, modulo 2^32.
+    pub block_timestamp_last: u32,
+}
+
+/// Tokens of a Uniswap V2 pair with their decimals, these never change for a pair.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairTokens {
+    pub token0: String,
+    pub token1: String,
+    pub decimals0: u8,
+    pub decimals1: u8,
+}
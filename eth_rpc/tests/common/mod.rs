@@ -0,0 +1,101 @@
+#![allow(dead_code)]
+
+pub use test_support::no_retry_policy;
+use test_support::Fixtures;
+use eth_rpc::EthRpcClient;
+use serde_json::{json, Value};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+use wiremock::matchers::{body_partial_json, method};
+
+pub const GENESIS_TIMESTAMP: u64 = 1_600_000_000;
+pub const BLOCK_TIME: u64 = 13;
+pub const HEAD: u64 = 1000;
+pub const PAIR: &str = "0x00000000000000000000000000000000000000a1";
+/// 18 decimals token0 of the pair.
+pub const TOKEN: &str = "0x00000000000000000000000000000000000000aa";
+/// 6 decimals token1 of the pair.
+pub const QUOTE: &str = "0x00000000000000000000000000000000000000bb";
+
+pub const FIXTURES: Fixtures = Fixtures::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures"));
+
+/// Local stand-in node and a client pointed at it.
+pub async fn stand_in() -> (MockServer, EthRpcClient) {
+    test_support::stand_in(|server| {
+        EthRpcClient::builder()
+            .url(&server.uri())
+            .request_policy(no_retry_policy())
+            .build()
+            .unwrap()
+    }).await
+}
+
+pub async fn serve_method(server: &MockServer, rpc_method: &str, status: u16, fixture_name: &str) {
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": rpc_method })))
+        .respond_with(FIXTURES.json_response(status, fixture_name))
+        .mount(server)
+        .await;
+}
+
+/// Serves a chain of `HEAD + 1` blocks mined every `BLOCK_TIME` seconds from `GENESIS_TIMESTAMP`,
+/// with a pair where `TOKEN` is worth `4 + block / 1000` `QUOTE`.
+pub async fn serve_chain(server: &MockServer) {
+    Mock::given(method("POST"))
+        .respond_with(Chain)
+        .mount(server)
+        .await;
+}
+
+pub fn block_timestamp(number: u64) -> u64 {
+    GENESIS_TIMESTAMP + number * BLOCK_TIME
+}
+
+struct Chain;
+
+impl Respond for Chain {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let request: Value = serde_json::from_slice(&request.body).unwrap();
+        let params = &request["params"];
+        let result = match request["method"].as_str().unwrap() {
+            "eth_blockNumber" => json!(format!("{:#x}", HEAD)),
+            "eth_getBlockByNumber" => match block_number(&params[0]) {
+                Some(number) => json!({
+                    "number": format!("{:#x}", number),
+                    "timestamp": format!("{:#x}", block_timestamp(number)),
+                    "hash": format!("0x{:064x}", number),
+                }),
+                None => Value::Null,
+            },
+            "eth_call" => {
+                let block = block_number(&params[1]).unwrap();
+                let to = params[0]["to"].as_str().unwrap();
+                json!(match params[0]["data"].as_str().unwrap() {
+                    "0x0dfe1681" => word(&TOKEN[2..]),
+                    "0xd21220a7" => word(&QUOTE[2..]),
+                    "0x313ce567" if to == TOKEN => word("12"),
+                    "0x313ce567" => word("6"),
+                    "0x0902f1ac" => format!(
+                        "0x{:064x}{:064x}{:064x}",
+                        1000 * 10u128.pow(18),
+                        (4000 + u128::from(block)) * 10u128.pow(6),
+                        block_timestamp(block)
+                    ),
+                    data => panic!("unexpected call {}", data),
+                })
+            }
+            unexpected => panic!("unexpected method {}", unexpected),
+        };
+        ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+    }
+}
+
+fn block_number(param: &Value) -> Option<u64> {
+    match param.as_str().unwrap() {
+        "latest" => Some(HEAD),
+        number => Some(u64::from_str_radix(number.trim_start_matches("0x"), 16).unwrap()).filter(|number| *number <= HEAD),
+    }
+}
+
+fn word(hex: &str) -> String {
+    format!("0x{:0>64}", hex)
+}
@@ -0,0 +1,184 @@
+mod common;
+
+use api_time::TimeRange;
+use common::{block_timestamp, serve_chain, serve_method, stand_in, GENESIS_TIMESTAMP, HEAD, PAIR, QUOTE, TOKEN};
+use eth_rpc::{BlockId, EthRpcClient, PairTokens, Reserves, RpcError, DEFAULT_NODE_URL};
+use serde_json::json;
+use wiremock::{Mock, ResponseTemplate};
+use wiremock::matchers::{body_partial_json, method};
+
+#[tokio::test]
+async fn block_number_success() {
+    let (server, client) = stand_in().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "jsonrpc": "2.0", "method": "eth_blockNumber", "params": [] })))
+        .respond_with(common::FIXTURES.json_response(200, "block_number.json"))
+        .mount(&server)
+        .await;
+
+    assert_eq!(client.block_number().await.unwrap(), 1000);
+}
+
+#[tokio::test]
+async fn block_by_number() {
+    let (server, client) = stand_in().await;
+    serve_chain(&server).await;
+
+    let block = client.block(BlockId::Number(42)).await.unwrap();
+    assert_eq!(block.number, 42);
+    assert_eq!(block.timestamp, block_timestamp(42));
+    assert_eq!(block.hash, format!("0x{:064x}", 42));
+    assert_eq!(client.block(BlockId::Latest).await.unwrap().number, HEAD);
+}
+
+#[tokio::test]
+async fn unknown_block_not_found() {
+    let (server, client) = stand_in().await;
+    serve_method(&server, "eth_getBlockByNumber", 200, "block_null.json").await;
+
+    assert!(matches!(client.block(BlockId::Number(5000)).await, Err(RpcError::BlockNotFound(5000))));
+}
+
+#[tokio::test]
+async fn rpc_error_object() {
+    let (server, client) = stand_in().await;
+    serve_method(&server, "eth_getBlockByNumber", 200, "rpc_error.json").await;
+
+    assert!(matches!(
+        client.block(BlockId::Latest).await,
+        Err(RpcError::Rpc { method: "eth_getBlockByNumber", code: -32000, message }) if message == "header not found"
+    ));
+}
+
+#[tokio::test]
+async fn http_error_status() {
+    let (server, client) = stand_in().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(503).set_body_string("unavailable"))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(client.block_number().await, Err(RpcError::HttpStatus(503))));
+}
+
+#[tokio::test]
+async fn block_by_timestamp_between_blocks() {
+    let (server, client) = stand_in().await;
+    serve_chain(&server).await;
+
+    // between block 723 and 724
+    let block = client.block_by_timestamp(block_timestamp(723) + 5).await.unwrap();
+    assert_eq!(block.number, 723);
+    let block = client.block_by_timestamp(block_timestamp(724)).await.unwrap();
+    assert_eq!(block.number, 724);
+}
+
+#[tokio::test]
+async fn block_by_timestamp_bounds() {
+    let (server, client) = stand_in().await;
+    serve_chain(&server).await;
+
+    assert_eq!(client.block_by_timestamp(GENESIS_TIMESTAMP).await.unwrap().number, 0);
+    assert_eq!(client.block_by_timestamp(block_timestamp(HEAD) + 600).await.unwrap().number, HEAD);
+    assert!(matches!(
+        client.block_by_timestamp(GENESIS_TIMESTAMP - 1).await,
+        Err(RpcError::NoBlockBefore(timestamp)) if timestamp == GENESIS_TIMESTAMP - 1
+    ));
+}
+
+#[tokio::test]
+async fn reserves_decoded() {
+    let (server, client) = stand_in().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({
+            "method": "eth_call",
+            "params": [{ "to": PAIR, "data": "0x0902f1ac" }, "0x2a"],
+        })))
+        .respond_with(common::FIXTURES.json_response(200, "reserves.json"))
+        .mount(&server)
+        .await;
+
+    assert_eq!(client.reserves(PAIR, BlockId::Number(42)).await.unwrap(), Reserves {
+        reserve0: 1000 * 10u128.pow(18),
+        reserve1: 4000 * 10u128.pow(6),
+        block_timestamp_last: 1624971895,
+    });
+}
+
+#[tokio::test]
+async fn short_reserves_invalid() {
+    let (server, client) = stand_in().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x" })))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(
+        client.reserves(PAIR, BlockId::Latest).await,
+        Err(RpcError::InvalidResult { method: "getReserves", result }) if result == "0x"
+    ));
+}
+
+#[tokio::test]
+async fn pair_tokens_with_decimals() {
+    let (server, client) = stand_in().await;
+    serve_chain(&server).await;
+
+    assert_eq!(client.pair_tokens(PAIR).await.unwrap(), PairTokens {
+        token0: TOKEN.into(),
+        token1: QUOTE.into(),
+        decimals0: 18,
+        decimals1: 6,
+    });
+}
+
+#[tokio::test]
+async fn pair_price_both_ways() {
+    let (server, client) = stand_in().await;
+    serve_chain(&server).await;
+
+    let price = client.pair_price(PAIR, TOKEN, BlockId::Number(500)).await.unwrap();
+    assert!((price - 4.5).abs() < 1e-9, "{}", price);
+    let price = client.pair_price(PAIR, &QUOTE.to_uppercase().replace("0X", "0x"), BlockId::Latest).await.unwrap();
+    assert!((price - 0.2).abs() < 1e-9, "{}", price);
+    assert!(matches!(
+        client.pair_price(PAIR, PAIR, BlockId::Latest).await,
+        Err(RpcError::TokenNotInPair { .. })
+    ));
+}
+
+#[tokio::test]
+async fn pair_perf_from_chain_head() {
+    let (server, client) = stand_in().await;
+    serve_chain(&server).await;
+
+    // an hour before the head is between block 723 and 724
+    let perf = client.pair_perf(PAIR, TOKEN, TimeRange::Hours(1)).await.unwrap();
+    let expected = (5.0 / 4.723 - 1.0) * 100.0;
+    assert!((perf - expected).abs() < 1e-9, "{} != {}", perf, expected);
+}
+
+#[tokio::test]
+async fn pair_perf_before_the_epoch() {
+    let (server, client) = stand_in().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": "eth_getBlockByNumber" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": { "number": "0x2", "timestamp": "0x1a", "hash": format!("0x{:064x}", 2) },
+        })))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(
+        client.pair_perf(PAIR, TOKEN, TimeRange::Hours(1)).await,
+        Err(RpcError::BeforeEpoch(TimeRange::Hours(1)))
+    ));
+}
+
+#[test]
+fn builder_defaults_to_local_node() {
+    assert_eq!(EthRpcClient::new().unwrap().url(), DEFAULT_NODE_URL);
+    assert_eq!(EthRpcClient::builder().url("http://node:8545").build().unwrap().url(), "http://node:8545");
+}
@@ -0,0 +1,257 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use api_policy::RequestPolicy;
+use api_time::{system_clock, Clock};
+use serde::de::DeserializeOwned;
+use serde_derive::Deserialize;
+use serde_json::Value;
+
+use crate::transfers::{Log, Transfer, TRANSFER_TOPIC};
+use crate::{EtherscanError, Result};
+
+pub const ETHERSCAN_API: &str = "https://api.etherscan.io/api";
+/// Environment variable overriding the api url, eg.: for a testnet or a local stand-in.
+pub const API_URL_VAR: &str = "ETHERSCAN_API_URL";
+/// Environment variable holding the api key.
+pub const API_KEY_VAR: &str = "ETHERSCAN_API_KEY";
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Logs per `getLogs` page, the most etherscan returns at once.
+pub const LOGS_PAGE_SIZE: usize = 1000;
+/// Most logs etherscan pages through for one `getLogs` query, `page × offset` can't go past it.
+pub const LOGS_RESULT_LIMIT: usize = 10_000;
+
+/// Every api response wraps the payload in the same envelope, `status` is `1` on success.
+#[derive(Debug, Deserialize)]
+struct Envelope {
+    status: String,
+    message: String,
+    result: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct EthPrice {
+    ethusd: String,
+}
+
+/// Etherscan api client, cloning is cheap, clones share the same connection pool.
+#[derive(Debug, Clone)]
+pub struct EtherscanClient {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    policy: Arc<RequestPolicy>,
+    clock: Arc<dyn Clock>,
+}
+
+impl EtherscanClient {
+    /// Client with the api url and key of the environment.
+    pub fn from_env() -> Result<Self> {
+        EtherscanClientBuilder::from_env().build()
+    }
+
+    pub fn builder() -> EtherscanClientBuilder {
+        EtherscanClientBuilder::default()
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Clock of "now" in [`EtherscanClient::last_block_number`].
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// Number of the last block mined at or before the unix timestamp.
+    pub async fn block_by_timestamp(&self, timestamp: u64) -> Result<i64> {
+        const ACTION: &str = "getblocknobytime";
+        let block: String = self.get("block", ACTION, &[("timestamp", timestamp.to_string()), ("closest", "before".into())]).await?;
+        block.parse().map_err(|_| EtherscanError::InvalidResult { action: ACTION, result: block })
+    }
+
+    /// Number of the last block mined by now.
+    pub async fn last_block_number(&self) -> Result<i64> {
+        self.block_by_timestamp(self.clock.now()).await
+    }
+
+    /// Last ETH price in USD.
+    pub async fn eth_price(&self) -> Result<f64> {
+        const ACTION: &str = "ethprice";
+        let price: EthPrice = self.get("stats", ACTION, &[]).await?;
+        price.ethusd.parse().map_err(|_| EtherscanError::InvalidResult { action: ACTION, result: price.ethusd })
+    }
+
+    /// ERC-20 `Transfer` events of the token contract between the blocks, both included, in chain order.
+    /// `to_block` `None` reads up to the chain head. A query reaching [`LOGS_RESULT_LIMIT`] is continued
+    /// from the block of its last log, [`EtherscanError::LogLimit`] when a single block holds more logs.
+    pub async fn transfers(&self, token: &str, from_block: i64, to_block: Option<i64>) -> Result<Vec<Transfer>> {
+        let mut transfers = Vec::new();
+        let mut window_start = from_block;
+        loop {
+            let (mut window, complete) = self.transfer_pages(token, window_start, to_block).await?;
+            if complete {
+                transfers.append(&mut window);
+                return Ok(transfers)
+            }
+            // the logs of the last block may continue past the limit, the next query reads that block again in whole
+            let last_block = window.last().map_or(window_start, |transfer| transfer.block);
+            if last_block == window_start {
+                return Err(EtherscanError::LogLimit(last_block))
+            }
+            window.retain(|transfer| transfer.block < last_block);
+            transfers.append(&mut window);
+            window_start = last_block;
+        }
+    }
+
+    // Transfers of one `getLogs` query, `false` when it stopped at the result limit with more logs left.
+    async fn transfer_pages(&self, token: &str, from_block: i64, to_block: Option<i64>) -> Result<(Vec<Transfer>, bool)> {
+        const ACTION: &str = "getLogs";
+        let to_block = to_block.map_or_else(|| "latest".to_string(), |block| block.to_string());
+        let mut transfers = Vec::new();
+        for page in 1..=LOGS_RESULT_LIMIT / LOGS_PAGE_SIZE {
+            let params = [
+                ("address", token.to_lowercase()),
+                ("fromBlock", from_block.to_string()),
+                ("toBlock", to_block.clone()),
+                ("topic0", TRANSFER_TOPIC.to_string()),
+                ("page", page.to_string()),
+                ("offset", LOGS_PAGE_SIZE.to_string()),
+            ];
+            let logs: Vec<Log> = match self.get("logs", ACTION, &params).await {
+                Ok(logs) => logs,
+                // an empty page is reported as an api error
+                Err(EtherscanError::Api { message, .. }) if message == "No records found" => Vec::new(),
+                Err(e) => return Err(e),
+            };
+            for log in &logs {
+                let transfer = Transfer::from_log(log)
+                    .ok_or_else(|| EtherscanError::InvalidResult { action: ACTION, result: format!("{:?}", log) })?;
+                transfers.push(transfer);
+            }
+            if logs.len() < LOGS_PAGE_SIZE {
+                return Ok((transfers, true))
+            }
+        }
+        Ok((transfers, false))
+    }
+
+    async fn get<T: DeserializeOwned>(&self, module: &str, action: &'static str, params: &[(&str, String)]) -> Result<T> {
+        let mut query = vec![("module", module.to_string()), ("action", action.to_string())];
+        query.extend(params.iter().map(|(name, value)| (*name, value.clone())));
+        if let Some(api_key) = &self.api_key {
+            query.push(("apikey", api_key.clone()));
+        }
+        let request = self.http.get(&self.base_url).query(&query).build()?;
+        let response = self.policy.execute(&self.http, request).await?;
+        let http_status = response.status();
+        let body = response.bytes().await?;
+
+        let envelope = match serde_json::from_slice::<Envelope>(&body) {
+            Ok(envelope) => envelope,
+            Err(_) if http_status.as_u16() == 429 => return Err(EtherscanError::RateLimited(format!("http status {}", http_status.as_u16()))),
+            Err(_) if !http_status.is_success() => return Err(EtherscanError::HttpStatus(http_status.as_u16())),
+            Err(source) => return Err(EtherscanError::Decode { action, source }),
+        };
+        if envelope.status != "1" {
+            let result = match envelope.result {
+                Value::String(result) => result,
+                result => result.to_string(),
+            };
+            // etherscan reports rate limits as a plain api error, eg.: `Max rate limit reached`
+            if result.to_lowercase().contains("rate limit") {
+                return Err(EtherscanError::RateLimited(result))
+            }
+            return Err(EtherscanError::Api { message: envelope.message, result })
+        }
+        serde_json::from_value(envelope.result).map_err(|source| EtherscanError::Decode { action, source })
+    }
+}
+
+/// Builder for [`EtherscanClient`], every option falls back to a sane default.
+#[derive(Debug, Clone)]
+pub struct EtherscanClientBuilder {
+    base_url: String,
+    api_key: Option<String>,
+    timeout: Duration,
+    policy: Option<Arc<RequestPolicy>>,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl Default for EtherscanClientBuilder {
+    fn default() -> Self {
+        Self {
+            base_url: ETHERSCAN_API.into(),
+            api_key: None,
+            timeout: DEFAULT_TIMEOUT,
+            policy: None,
+            clock: None,
+        }
+    }
+}
+
+impl EtherscanClientBuilder {
+    /// Builder starting from the [`API_URL_VAR`] and [`API_KEY_VAR`] environment variables, where set.
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Builder starting from the [`API_URL_VAR`] and [`API_KEY_VAR`] values `var` looks up, where set.
+    pub fn from_vars<F>(var: F) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut builder = Self::default();
+        if let Some(url) = var(API_URL_VAR) {
+            builder = builder.base_url(&url);
+        }
+        if let Some(api_key) = var(API_KEY_VAR) {
+            builder = builder.api_key(&api_key);
+        }
+        builder
+    }
+
+    /// Api url, eg.: `https://api-goerli.etherscan.io/api` for a testnet or a local mock server.
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').into();
+        self
+    }
+
+    /// Api key sent with every call, the keyless calls are limited to one per 5 seconds.
+    pub fn api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.into()).filter(|api_key: &String| !api_key.is_empty());
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Retry and rate limit policy, the process wide [`RequestPolicy::global`] if not set.
+    pub fn request_policy(mut self, policy: Arc<RequestPolicy>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Clock of "now", the system clock if not set.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    pub fn build(self) -> Result<EtherscanClient> {
+        let http = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .connect_timeout(self.timeout)
+            .build()?;
+        Ok(EtherscanClient {
+            http,
+            base_url: self.base_url,
+            api_key: self.api_key,
+            policy: self.policy.unwrap_or_else(RequestPolicy::global),
+            clock: self.clock.unwrap_or_else(system_clock),
+        })
+    }
+}
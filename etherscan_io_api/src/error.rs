@@ -0,0 +1,37 @@
+use thiserror::Error;
+
+pub type Result<T, E = EtherscanError> = std::result::Result<T, E>;
+
+#[derive(Debug, Error)]
+pub enum EtherscanError {
+    /// Connection, timeout or other transport level failure.
+    #[error("etherscan request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// Non success http status without a parsable api response.
+    #[error("etherscan responded with http status {0}")]
+    HttpStatus(u16),
+    /// The response body doesn't match the `status`/`message`/`result` envelope or the expected result.
+    #[error("failed to decode the etherscan {action} response: {source}")]
+    Decode {
+        action: &'static str,
+        source: serde_json::Error,
+    },
+    /// The api key, or the keyless caller, went over its call rate limit.
+    #[error("etherscan rate limit reached: {0}")]
+    RateLimited(String),
+    /// The api responded with status `0`, the error text is in `result`.
+    #[error("etherscan responded with {message}: {result}")]
+    Api { message: String, result: String },
+    /// The result doesn't hold the expected value, eg.: a block number that isn't a decimal.
+    #[error("unexpected etherscan {action} result: {result}")]
+    InvalidResult { action: &'static str, result: String },
+    /// A block holds more transfer logs than etherscan lists for one query, the transfers would be cut short.
+    #[error("block {0} holds more logs than etherscan lists for one query")]
+    LogLimit(i64),
+    /// The block anchors file can't be read or written.
+    #[error("block anchors file {path}: {source}")]
+    AnchorFile { path: String, source: std::io::Error },
+    /// The block anchors file isn't a json list of anchors.
+    #[error("block anchors file {path} is malformed: {source}")]
+    AnchorDecode { path: String, source: serde_json::Error },
+}
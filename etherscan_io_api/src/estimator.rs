@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use api_time::Clock;
+use serde_derive::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{EtherscanClient, EtherscanError, Result};
+
+/// Anchors closer than this to a timestamp give its block without a lookup.
+pub const DEFAULT_MAX_GAP: u64 = 15 * 60;
+/// Seconds per block the estimate extrapolates with past the last anchor, the proof of stake slot time.
+pub const DEFAULT_BLOCK_TIME: f64 = 12.0;
+
+// Saves of this process, numbering the temporary anchor files.
+static SAVES: AtomicUsize = AtomicUsize::new(0);
+
+/// Block mined at or before the timestamp, confirmed by a lookup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Anchor {
+    pub timestamp: u64,
+    pub block: i64,
+}
+
+/// Block numbers of timestamps interpolated between known anchors, so repeated lookups of nearby timestamps
+/// don't call etherscan. A timestamp without an anchor within the max gap is looked up once and becomes an anchor,
+/// the anchors are saved to the cache file, if set, after each lookup.
+/// Cloning is cheap, clones share the anchors.
+#[derive(Debug, Clone)]
+pub struct BlockEstimator {
+    etherscan: EtherscanClient,
+    anchors: Arc<Mutex<BTreeMap<u64, i64>>>,
+    // Held while the cache file is written, so the saves of the clones don't overtake each other.
+    saving: Arc<tokio::sync::Mutex<()>>,
+    cache_file: Option<PathBuf>,
+    max_gap: u64,
+    block_time: f64,
+    clock: Arc<dyn Clock>,
+}
+
+impl BlockEstimator {
+    /// Estimator without a cache file, the anchors only last as long as the estimator.
+    pub fn new(etherscan: EtherscanClient) -> Self {
+        BlockEstimator {
+            clock: Arc::clone(etherscan.clock()),
+            etherscan,
+            anchors: Arc::new(Mutex::new(BTreeMap::new())),
+            saving: Arc::default(),
+            cache_file: None,
+            max_gap: DEFAULT_MAX_GAP,
+            block_time: DEFAULT_BLOCK_TIME,
+        }
+    }
+
+    pub fn builder() -> BlockEstimatorBuilder {
+        BlockEstimatorBuilder::default()
+    }
+
+    pub fn etherscan(&self) -> &EtherscanClient {
+        &self.etherscan
+    }
+
+    /// Clock of "now", future timestamps don't become anchors.
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// Known anchors in timestamp order.
+    pub fn anchors(&self) -> Vec<Anchor> {
+        self.lock().iter().map(|(&timestamp, &block)| Anchor { timestamp, block }).collect()
+    }
+
+    /// Number of the last block mined at or before the unix timestamp, estimated from the anchors
+    /// or looked up on etherscan when no anchor is within the max gap.
+    pub async fn block_at(&self, timestamp: u64) -> Result<i64> {
+        if let Some(block) = self.estimate(timestamp) {
+            return Ok(block)
+        }
+        let block = self.etherscan.block_by_timestamp(timestamp).await?;
+        // a future timestamp gets the chain head, which isn't its block
+        if timestamp <= self.clock.now() {
+            self.lock().insert(timestamp, block);
+            // the lookup succeeded, a cache that can't be written only costs lookups after a restart
+            if let Err(e) = self.save().await {
+                warn!("{}", e);
+            }
+        }
+        Ok(block)
+    }
+
+    /// Number of the last block mined by now.
+    pub async fn last_block_number(&self) -> Result<i64> {
+        self.block_at(self.clock.now()).await
+    }
+
+    /// Block of the timestamp without a lookup, `None` when no anchor is within the max gap.
+    /// Between two anchors the block is interpolated, past the first or last anchor it's extrapolated with the block time.
+    pub fn estimate(&self, timestamp: u64) -> Option<i64> {
+        let anchors = self.lock();
+        let before = anchors.range(..=timestamp).next_back().map(|(&timestamp, &block)| Anchor { timestamp, block });
+        let after = anchors.range(timestamp..).next().map(|(&timestamp, &block)| Anchor { timestamp, block });
+        let gap = |anchor: &Anchor| anchor.timestamp.max(timestamp) - anchor.timestamp.min(timestamp);
+        let nearest = before.iter().chain(after.iter()).min_by_key(|anchor| gap(anchor))?;
+        if gap(nearest) > self.max_gap {
+            return None
+        }
+        match (before, after) {
+            (Some(before), _) if before.timestamp == timestamp => Some(before.block),
+            (Some(before), Some(after)) => {
+                let blocks = (after.block - before.block) as f64;
+                let share = (timestamp - before.timestamp) as f64 / (after.timestamp - before.timestamp) as f64;
+                Some(before.block + (blocks * share).floor() as i64)
+            },
+            _ => {
+                let seconds = timestamp as f64 - nearest.timestamp as f64;
+                Some(nearest.block + (seconds / self.block_time).floor() as i64)
+            },
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<u64, i64>> {
+        self.anchors.lock().expect("block anchors poisoned")
+    }
+
+    // Written to a temporary file first, so a crash mid write keeps the previous anchors.
+    // Clones save one at a time and read the anchors once it's their turn, so the last save holds every anchor.
+    // The temporary file is unique per save for estimators that don't share the lock.
+    async fn save(&self) -> Result<()> {
+        let cache_file = match &self.cache_file {
+            Some(cache_file) => cache_file,
+            None => return Ok(()),
+        };
+        let _saving = self.saving.lock().await;
+        let anchors = serde_json::to_vec(&self.anchors()).map_err(|source| anchor_decode(cache_file, source))?;
+        let temporary = cache_file.with_extension(format!("{}.{}.tmp", std::process::id(), SAVES.fetch_add(1, Ordering::Relaxed)));
+        let written = match tokio::fs::write(&temporary, anchors).await {
+            Ok(()) => tokio::fs::rename(&temporary, cache_file).await,
+            Err(e) => Err(e),
+        };
+        if let Err(source) = written {
+            let _ = tokio::fs::remove_file(&temporary).await;
+            return Err(anchor_file(cache_file, source))
+        }
+        Ok(())
+    }
+}
+
+fn load(cache_file: &Path) -> Result<BTreeMap<u64, i64>> {
+    let anchors = match std::fs::read(cache_file) {
+        Ok(anchors) => anchors,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(source) => return Err(anchor_file(cache_file, source)),
+    };
+    let anchors: Vec<Anchor> = serde_json::from_slice(&anchors).map_err(|source| anchor_decode(cache_file, source))?;
+    Ok(anchors.into_iter().map(|anchor| (anchor.timestamp, anchor.block)).collect())
+}
+
+fn anchor_file(cache_file: &Path, source: std::io::Error) -> EtherscanError {
+    EtherscanError::AnchorFile { path: cache_file.display().to_string(), source }
+}
+
+fn anchor_decode(cache_file: &Path, source: serde_json::Error) -> EtherscanError {
+    EtherscanError::AnchorDecode { path: cache_file.display().to_string(), source }
+}
+
+/// Builder for [`BlockEstimator`], every option falls back to a sane default.
+#[derive(Debug, Clone)]
+pub struct BlockEstimatorBuilder {
+    etherscan: Option<EtherscanClient>,
+    cache_file: Option<PathBuf>,
+    max_gap: u64,
+    block_time: f64,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl Default for BlockEstimatorBuilder {
+    fn default() -> Self {
+        Self {
+            etherscan: None,
+            cache_file: None,
+            max_gap: DEFAULT_MAX_GAP,
+            block_time: DEFAULT_BLOCK_TIME,
+            clock: None,
+        }
+    }
+}
+
+impl BlockEstimatorBuilder {
+    /// Etherscan client of the confirming lookups, [`EtherscanClient::from_env`] if not set.
+    pub fn etherscan(mut self, etherscan: EtherscanClient) -> Self {
+        self.etherscan = Some(etherscan);
+        self
+    }
+
+    /// Json file the anchors are loaded from and saved to, a missing file starts without anchors.
+    pub fn cache_file(mut self, cache_file: impl Into<PathBuf>) -> Self {
+        self.cache_file = Some(cache_file.into());
+        self
+    }
+
+    /// Seconds an anchor may be away from a timestamp to estimate its block, 0 looks up every timestamp without an exact anchor.
+    pub fn max_gap(mut self, max_gap: u64) -> Self {
+        self.max_gap = max_gap;
+        self
+    }
+
+    /// Average seconds per block, for the estimates past the first or last anchor.
+    pub fn block_time(mut self, block_time: f64) -> Self {
+        self.block_time = block_time;
+        self
+    }
+
+    /// Clock of "now", the clock of the etherscan client if not set.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    pub fn build(self) -> Result<BlockEstimator> {
+        let etherscan = match self.etherscan {
+            Some(etherscan) => etherscan,
+            None => EtherscanClient::from_env()?,
+        };
+        let anchors = match &self.cache_file {
+            Some(cache_file) => load(cache_file)?,
+            None => BTreeMap::new(),
+        };
+        Ok(BlockEstimator {
+            clock: self.clock.unwrap_or_else(|| Arc::clone(etherscan.clock())),
+            etherscan,
+            anchors: Arc::new(Mutex::new(anchors)),
+            saving: Arc::default(),
+            cache_file: self.cache_file,
+            max_gap: self.max_gap,
+            block_time: self.block_time,
+        })
+    }
+}
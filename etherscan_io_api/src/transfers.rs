@@ -0,0 +1,115 @@
+use std::convert::TryFrom;
+
+use serde_derive::Deserialize;
+
+/// `keccak256("Transfer(address,address,uint256)")`, topic0 of the ERC-20 transfer event.
+pub const TRANSFER_TOPIC: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+/// Tokens are minted from and burned to the zero address.
+pub const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// Event log as `getLogs` returns it, the numbers are hex quantities.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Log {
+    topics: Vec<String>,
+    data: String,
+    block_number: String,
+    time_stamp: String,
+    transaction_hash: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferKind {
+    /// New tokens, sent from the zero address.
+    Mint,
+    /// Redeemed tokens, sent to the zero address.
+    Burn,
+    Transfer,
+}
+
+/// ERC-20 `Transfer` event, `value` in the smallest token unit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transfer {
+    pub block: i64,
+    pub timestamp: u64,
+    pub tx_hash: String,
+    pub from: String,
+    pub to: String,
+    pub value: u128,
+}
+
+impl Transfer {
+    pub fn kind(&self) -> TransferKind {
+        if self.from == ZERO_ADDRESS {
+            TransferKind::Mint
+        } else if self.to == ZERO_ADDRESS {
+            TransferKind::Burn
+        } else {
+            TransferKind::Transfer
+        }
+    }
+
+    // `None` for a malformed log or one that isn't an ERC-20 transfer, eg.: an ERC-721 transfer with the token id as a fourth topic.
+    pub(crate) fn from_log(log: &Log) -> Option<Self> {
+        match log.topics.as_slice() {
+            [topic, from, to] if topic.eq_ignore_ascii_case(TRANSFER_TOPIC) => Some(Transfer {
+                block: i64::from_str_radix(hex_digits(&log.block_number)?, 16).ok()?,
+                timestamp: u64::from_str_radix(hex_digits(&log.time_stamp)?, 16).ok()?,
+                tx_hash: log.transaction_hash.to_lowercase(),
+                from: topic_address(from)?,
+                to: topic_address(to)?,
+                value: u128::from_str_radix(hex_digits(&log.data)?, 16).ok()?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Tokens minted and burned by a list of transfers, in the smallest token unit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenFlows {
+    pub minted: u128,
+    pub burned: u128,
+    pub mints: usize,
+    pub burns: usize,
+}
+
+impl TokenFlows {
+    pub fn from_transfers(transfers: &[Transfer]) -> Self {
+        transfers.iter().fold(TokenFlows::default(), |mut flows, transfer| {
+            match transfer.kind() {
+                TransferKind::Mint => {
+                    flows.minted = flows.minted.saturating_add(transfer.value);
+                    flows.mints += 1;
+                },
+                TransferKind::Burn => {
+                    flows.burned = flows.burned.saturating_add(transfer.value);
+                    flows.burns += 1;
+                },
+                TransferKind::Transfer => {},
+            }
+            flows
+        })
+    }
+
+    /// Minted less burned tokens, negative when more were redeemed than issued.
+    pub fn net(&self) -> i128 {
+        let minted = i128::try_from(self.minted).unwrap_or(i128::MAX);
+        let burned = i128::try_from(self.burned).unwrap_or(i128::MAX);
+        minted.saturating_sub(burned)
+    }
+}
+
+// Hex digits of a quantity, `0x` alone is zero.
+fn hex_digits(quantity: &str) -> Option<&str> {
+    match quantity.strip_prefix("0x")? {
+        "" => Some("0"),
+        digits => Some(digits),
+    }
+}
+
+// Address of a 32 byte topic, the last 20 bytes.
+fn topic_address(topic: &str) -> Option<String> {
+    let digits = topic.strip_prefix("0x").filter(|digits| digits.len() == 64)?;
+    Some(format!("0x{}", &digits[24..]).to_lowercase())
+}
@@ -0,0 +1,32 @@
+#![allow(dead_code)]
+
+pub use test_support::no_retry_policy;
+use test_support::Fixtures;
+use etherscan_io_api::{EtherscanClient, EtherscanClientBuilder};
+use wiremock::{Mock, MockServer};
+use wiremock::matchers::{method, path, query_param};
+
+pub const API_KEY: &str = "TESTKEY";
+
+pub const FIXTURES: Fixtures = Fixtures::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures"));
+
+/// Builder pointed at the stand-in, without retries.
+pub fn builder(server: &MockServer) -> EtherscanClientBuilder {
+    EtherscanClient::builder()
+        .base_url(&format!("{}/api", server.uri()))
+        .request_policy(no_retry_policy())
+}
+
+/// Local stand-in for the etherscan api and a client with an api key pointed at it.
+pub async fn stand_in() -> (MockServer, EtherscanClient) {
+    test_support::stand_in(|server| builder(server).api_key(API_KEY).build().unwrap()).await
+}
+
+pub async fn serve_action(server: &MockServer, action: &str, status: u16, fixture_name: &str) {
+    Mock::given(method("GET"))
+        .and(path("/api"))
+        .and(query_param("action", action))
+        .respond_with(FIXTURES.json_response(status, fixture_name))
+        .mount(server)
+        .await;
+}
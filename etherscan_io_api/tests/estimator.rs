@@ -0,0 +1,189 @@
+mod common;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use api_time::FixedClock;
+use common::stand_in;
+use etherscan_io_api::{Anchor, BlockEstimator, EtherscanClient, EtherscanError};
+use wiremock::{Mock, MockServer};
+use wiremock::matchers::{method, path, query_param};
+
+const TIMESTAMP: u64 = 1_624_961_399;
+const DAY: u64 = 86_400;
+
+// Anchor file in the temp dir, unique per test so they can run in parallel.
+fn cache_file(test: &str) -> PathBuf {
+    let cache_file = std::env::temp_dir().join(format!("etherscan_io_api_{}_{}.json", test, std::process::id()));
+    let _ = std::fs::remove_file(&cache_file);
+    cache_file
+}
+
+fn write_anchors(cache_file: &PathBuf, anchors: &[Anchor]) {
+    std::fs::write(cache_file, serde_json::to_string(anchors).unwrap()).unwrap();
+}
+
+async fn serve_block(server: &MockServer, timestamp: u64, expected_calls: u64) {
+    Mock::given(method("GET"))
+        .and(path("/api"))
+        .and(query_param("action", "getblocknobytime"))
+        .and(query_param("timestamp", timestamp.to_string().as_str()))
+        .respond_with(common::FIXTURES.json_response(200, "block.json"))
+        .expect(expected_calls)
+        .mount(server)
+        .await;
+}
+
+fn estimator(client: EtherscanClient, cache_file: &PathBuf) -> BlockEstimator {
+    BlockEstimator::builder().etherscan(client).cache_file(cache_file).build().unwrap()
+}
+
+#[tokio::test]
+async fn nearby_timestamps_need_one_lookup() {
+    let (server, client) = stand_in().await;
+    serve_block(&server, TIMESTAMP, 1).await;
+    let estimator = BlockEstimator::new(client);
+
+    assert_eq!(estimator.block_at(TIMESTAMP).await.unwrap(), 1000);
+    assert_eq!(estimator.block_at(TIMESTAMP).await.unwrap(), 1000);
+    // a minute later is 5 blocks of 12 seconds on
+    assert_eq!(estimator.block_at(TIMESTAMP + 60).await.unwrap(), 1005);
+    assert_eq!(estimator.block_at(TIMESTAMP - 60).await.unwrap(), 995);
+}
+
+#[tokio::test]
+async fn far_timestamps_are_looked_up() {
+    let (server, client) = stand_in().await;
+    serve_block(&server, TIMESTAMP, 1).await;
+    serve_block(&server, TIMESTAMP - DAY, 1).await;
+    let estimator = BlockEstimator::new(client);
+
+    estimator.block_at(TIMESTAMP).await.unwrap();
+    estimator.block_at(TIMESTAMP - DAY).await.unwrap();
+    assert_eq!(estimator.anchors().len(), 2);
+}
+
+#[tokio::test]
+async fn interpolates_between_anchors() {
+    let (_server, client) = stand_in().await;
+    let cache_file = cache_file("interpolates");
+    write_anchors(&cache_file, &[
+        Anchor { timestamp: TIMESTAMP, block: 1000 },
+        Anchor { timestamp: TIMESTAMP + 1000, block: 1100 },
+    ]);
+    let estimator = estimator(client, &cache_file);
+
+    // without mocks a lookup would fail
+    assert_eq!(estimator.block_at(TIMESTAMP + 500).await.unwrap(), 1050);
+    assert_eq!(estimator.block_at(TIMESTAMP + 999).await.unwrap(), 1099);
+    assert_eq!(estimator.estimate(TIMESTAMP + 1000 + DAY), None);
+    std::fs::remove_file(&cache_file).unwrap();
+}
+
+#[tokio::test]
+async fn anchors_are_saved_and_reloaded() {
+    let (server, client) = stand_in().await;
+    serve_block(&server, TIMESTAMP, 1).await;
+    let cache_file = cache_file("saved");
+
+    estimator(client.clone(), &cache_file).block_at(TIMESTAMP).await.unwrap();
+
+    let reloaded = estimator(client, &cache_file);
+    assert_eq!(reloaded.anchors(), vec![Anchor { timestamp: TIMESTAMP, block: 1000 }]);
+    assert_eq!(reloaded.block_at(TIMESTAMP + 12).await.unwrap(), 1001);
+    std::fs::remove_file(&cache_file).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_lookups_of_clones_are_all_saved() {
+    let (server, client) = stand_in().await;
+    let timestamps: Vec<u64> = (0..8).map(|days| TIMESTAMP - days * DAY).collect();
+    for &timestamp in &timestamps {
+        serve_block(&server, timestamp, 1).await;
+    }
+    let cache_file = cache_file("concurrent");
+    let shared = estimator(client.clone(), &cache_file);
+
+    let lookups: Vec<_> = timestamps.iter()
+        .map(|&timestamp| {
+            let clone = shared.clone();
+            tokio::spawn(async move { clone.block_at(timestamp).await })
+        })
+        .collect();
+    for lookup in lookups {
+        lookup.await.unwrap().unwrap();
+    }
+    assert_eq!(estimator(client, &cache_file).anchors().len(), timestamps.len());
+    std::fs::remove_file(&cache_file).unwrap();
+}
+
+#[tokio::test]
+async fn future_timestamps_are_not_anchored() {
+    let (server, client) = stand_in().await;
+    Mock::given(method("GET"))
+        .and(path("/api"))
+        .respond_with(common::FIXTURES.json_response(200, "block.json"))
+        .mount(&server)
+        .await;
+    let estimator = BlockEstimator::builder().etherscan(client).clock(Arc::new(FixedClock::new(TIMESTAMP))).build().unwrap();
+
+    estimator.block_at(TIMESTAMP + 1).await.unwrap();
+    assert!(estimator.anchors().is_empty());
+    assert_eq!(estimator.last_block_number().await.unwrap(), 1000);
+    assert_eq!(estimator.anchors(), vec![Anchor { timestamp: TIMESTAMP, block: 1000 }]);
+}
+
+#[tokio::test]
+async fn malformed_cache_file() {
+    let (_server, client) = stand_in().await;
+    let cache_file = cache_file("malformed");
+    std::fs::write(&cache_file, "{}").unwrap();
+
+    let error = BlockEstimator::builder().etherscan(client).cache_file(&cache_file).build().unwrap_err();
+    assert!(matches!(error, EtherscanError::AnchorDecode { .. }));
+    std::fs::remove_file(&cache_file).unwrap();
+}
+
+#[tokio::test]
+async fn missing_cache_file_starts_empty() {
+    let (_server, client) = stand_in().await;
+    let estimator = estimator(client, &cache_file("missing"));
+
+    assert!(estimator.anchors().is_empty());
+    assert_eq!(estimator.estimate(TIMESTAMP), None);
+}
+
+#[tokio::test]
+async fn unwritable_cache_file_keeps_the_block() {
+    let (server, client) = stand_in().await;
+    serve_block(&server, TIMESTAMP, 1).await;
+    let cache_file = std::env::temp_dir().join(format!("etherscan_io_api_missing_{}", std::process::id())).join("anchors.json");
+    let estimator = estimator(client, &cache_file);
+
+    assert_eq!(estimator.block_at(TIMESTAMP).await.unwrap(), 1000);
+    assert_eq!(estimator.anchors(), vec![Anchor { timestamp: TIMESTAMP, block: 1000 }]);
+}
+
+#[tokio::test]
+async fn concurrent_clones_save() {
+    let (server, client) = stand_in().await;
+    Mock::given(method("GET"))
+        .and(path("/api"))
+        .respond_with(common::FIXTURES.json_response(200, "block.json"))
+        .mount(&server)
+        .await;
+    let cache_file = cache_file("concurrent");
+    let shared = BlockEstimator::builder().etherscan(client.clone()).cache_file(&cache_file).max_gap(0).build().unwrap();
+
+    let lookups: Vec<_> = (0..8u64)
+        .map(|day| {
+            let shared = shared.clone();
+            tokio::spawn(async move { shared.block_at(TIMESTAMP - day * DAY).await })
+        })
+        .collect();
+    for lookup in lookups {
+        assert_eq!(lookup.await.unwrap().unwrap(), 1000);
+    }
+    assert_eq!(estimator(client, &cache_file).anchors().len(), 8);
+    std::fs::remove_file(&cache_file).unwrap();
+}
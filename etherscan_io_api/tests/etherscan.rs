@@ -0,0 +1,168 @@
+mod common;
+
+use common::{builder, serve_action, stand_in, API_KEY};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use api_time::FixedClock;
+use etherscan_io_api::{EtherscanClientBuilder, EtherscanError, API_KEY_VAR, API_URL_VAR, ETHERSCAN_API};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+use wiremock::matchers::{method, path, query_param, query_param_is_missing};
+
+#[tokio::test]
+async fn block_by_timestamp_success() {
+    let (server, client) = stand_in().await;
+    Mock::given(method("GET"))
+        .and(path("/api"))
+        .and(query_param("module", "block"))
+        .and(query_param("action", "getblocknobytime"))
+        .and(query_param("timestamp", "1624961399"))
+        .and(query_param("closest", "before"))
+        .and(query_param("apikey", API_KEY))
+        .respond_with(common::FIXTURES.json_response(200, "block.json"))
+        .mount(&server)
+        .await;
+
+    assert_eq!(client.block_by_timestamp(1624961399).await.unwrap(), 1000);
+}
+
+#[tokio::test]
+async fn keyless_calls_have_no_api_key() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api"))
+        .and(query_param_is_missing("apikey"))
+        .respond_with(common::FIXTURES.json_response(200, "block.json"))
+        .mount(&server)
+        .await;
+
+    let client = builder(&server).api_key("").build().unwrap();
+    assert_eq!(client.block_by_timestamp(1624961399).await.unwrap(), 1000);
+}
+
+#[tokio::test]
+async fn block_by_timestamp_malformed() {
+    let (server, client) = stand_in().await;
+    serve_action(&server, "getblocknobytime", 200, "block_malformed.json").await;
+
+    assert!(matches!(
+        client.block_by_timestamp(1624961399).await,
+        Err(EtherscanError::InvalidResult { action: "getblocknobytime", result }) if result == "0x3e8"
+    ));
+}
+
+#[tokio::test]
+async fn block_by_timestamp_error() {
+    let (server, client) = stand_in().await;
+    serve_action(&server, "getblocknobytime", 200, "block_error.json").await;
+
+    assert!(matches!(
+        client.block_by_timestamp(0).await,
+        Err(EtherscanError::Api { message, result }) if message == "NOTOK" && result == "Error! Invalid timestamp"
+    ));
+}
+
+#[tokio::test]
+async fn block_by_timestamp_http_error() {
+    let (server, client) = stand_in().await;
+    Mock::given(method("GET"))
+        .and(path("/api"))
+        .respond_with(ResponseTemplate::new(503).set_body_string("Service Unavailable"))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(client.block_by_timestamp(1624961399).await, Err(EtherscanError::HttpStatus(503))));
+}
+
+#[tokio::test]
+async fn too_many_requests_is_rate_limited() {
+    let (server, client) = stand_in().await;
+    Mock::given(method("GET"))
+        .and(path("/api"))
+        .respond_with(ResponseTemplate::new(429).set_body_string("Too Many Requests"))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(client.block_by_timestamp(1624961399).await, Err(EtherscanError::RateLimited(_))));
+}
+
+#[tokio::test]
+async fn last_block_number_success() {
+    let (server, client) = stand_in().await;
+    serve_action(&server, "getblocknobytime", 200, "block.json").await;
+
+    assert_eq!(client.last_block_number().await.unwrap(), 1000);
+}
+
+#[tokio::test]
+async fn last_block_number_at_the_clock_time() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api"))
+        .and(query_param("timestamp", "1624961399"))
+        .respond_with(common::FIXTURES.json_response(200, "block.json"))
+        .mount(&server)
+        .await;
+
+    let client = builder(&server).clock(Arc::new(FixedClock::new(1624961399))).build().unwrap();
+    assert_eq!(client.last_block_number().await.unwrap(), 1000);
+}
+
+#[tokio::test]
+async fn last_block_number_rate_limited() {
+    let (server, client) = stand_in().await;
+    serve_action(&server, "getblocknobytime", 200, "rate_limited.json").await;
+
+    assert!(matches!(
+        client.last_block_number().await,
+        Err(EtherscanError::RateLimited(result)) if result == "Max rate limit reached"
+    ));
+}
+
+#[tokio::test]
+async fn eth_price_success() {
+    let (server, client) = stand_in().await;
+    serve_action(&server, "ethprice", 200, "eth_price.json").await;
+
+    assert!((client.eth_price().await.unwrap() - 2123.45).abs() < f64::EPSILON);
+}
+
+#[tokio::test]
+async fn eth_price_error() {
+    let (server, client) = stand_in().await;
+    serve_action(&server, "ethprice", 200, "rate_limited.json").await;
+
+    assert!(matches!(client.eth_price().await, Err(EtherscanError::RateLimited(_))));
+}
+
+#[tokio::test]
+async fn eth_price_malformed() {
+    let (server, client) = stand_in().await;
+    serve_action(&server, "ethprice", 200, "block.json").await;
+
+    assert!(matches!(client.eth_price().await, Err(EtherscanError::Decode { action: "ethprice", .. })));
+}
+
+#[tokio::test]
+async fn builder_from_vars() {
+    let client = EtherscanClientBuilder::from_vars(|_| None).build().unwrap();
+    assert_eq!(client.base_url(), ETHERSCAN_API);
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api"))
+        .and(query_param("apikey", API_KEY))
+        .respond_with(common::FIXTURES.json_response(200, "block.json"))
+        .mount(&server)
+        .await;
+    let mut vars = HashMap::new();
+    vars.insert(API_URL_VAR, format!("{}/api/", server.uri()));
+    vars.insert(API_KEY_VAR, API_KEY.to_string());
+    let client = EtherscanClientBuilder::from_vars(|name| vars.get(name).cloned())
+        .request_policy(common::no_retry_policy())
+        .build()
+        .unwrap();
+
+    assert_eq!(client.base_url(), format!("{}/api", server.uri()));
+    assert_eq!(client.block_by_timestamp(1624961399).await.unwrap(), 1000);
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use common::{serve_action, stand_in};
+use etherscan_io_api::{EtherscanError, TokenFlows, TransferKind, ICAP, LOGS_PAGE_SIZE, LOGS_RESULT_LIMIT, TRANSFER_TOPIC, ZERO_ADDRESS};
+use serde_json::json;
+use wiremock::{Mock, ResponseTemplate};
+use wiremock::matchers::{method, path, query_param};
+
+const HOLDER: &str = "0x000000000000000000000000000000000000a11c";
+const TOKENS: u128 = 1_000_000_000_000_000_000;
+
+#[tokio::test]
+async fn transfers_classified() {
+    let (server, client) = stand_in().await;
+    Mock::given(method("GET"))
+        .and(path("/api"))
+        .and(query_param("module", "logs"))
+        .and(query_param("action", "getLogs"))
+        .and(query_param("address", ICAP))
+        .and(query_param("fromBlock", "12951000"))
+        .and(query_param("toBlock", "latest"))
+        .and(query_param("topic0", TRANSFER_TOPIC))
+        .and(query_param("page", "1"))
+        .respond_with(common::FIXTURES.json_response(200, "logs.json"))
+        .mount(&server)
+        .await;
+
+    let transfers = client.transfers(&ICAP.to_uppercase().replace("0X", "0x"), 12_951_000, None).await.unwrap();
+    let kinds: Vec<TransferKind> = transfers.iter().map(|transfer| transfer.kind()).collect();
+    assert_eq!(kinds, vec![TransferKind::Mint, TransferKind::Transfer, TransferKind::Burn]);
+
+    let mint = &transfers[0];
+    assert_eq!(mint.block, 12_951_713);
+    assert_eq!(mint.timestamp, 1_624_989_495);
+    assert_eq!(mint.from, ZERO_ADDRESS);
+    assert_eq!(mint.to, HOLDER);
+    assert_eq!(mint.value, 2000 * TOKENS);
+}
+
+#[tokio::test]
+async fn flows_of_mints_and_burns() {
+    let (server, client) = stand_in().await;
+    serve_action(&server, "getLogs", 200, "logs.json").await;
+
+    let transfers = client.transfers(ICAP, 12_951_000, Some(12_952_000)).await.unwrap();
+    let flows = TokenFlows::from_transfers(&transfers);
+    assert_eq!(flows, TokenFlows { minted: 2000 * TOKENS, burned: 200 * TOKENS, mints: 1, burns: 1 });
+    assert_eq!(flows.net(), 1800 * TOKENS as i128);
+    assert_eq!(TokenFlows { burned: 5, ..TokenFlows::default() }.net(), -5);
+}
+
+#[tokio::test]
+async fn no_records_is_empty() {
+    let (server, client) = stand_in().await;
+    serve_action(&server, "getLogs", 200, "logs_empty.json").await;
+
+    assert!(client.transfers(ICAP, 12_951_000, None).await.unwrap().is_empty());
+}
+
+// Page of `logs` mints in `block`.
+fn page(block: i64, logs: usize) -> ResponseTemplate {
+    let log = json!({
+        "topics": [TRANSFER_TOPIC, format!("0x{:0>64}", &ZERO_ADDRESS[2..]), format!("0x{:0>64}", &HOLDER[2..])],
+        "data": "0x01",
+        "blockNumber": format!("{:#x}", block),
+        "timeStamp": "0x20",
+        "transactionHash": "0xaa",
+    });
+    ResponseTemplate::new(200).set_body_json(json!({ "status": "1", "message": "OK", "result": vec![log; logs] }))
+}
+
+async fn serve_page(server: &wiremock::MockServer, from_block: i64, page_number: usize, response: ResponseTemplate) {
+    Mock::given(method("GET"))
+        .and(query_param("fromBlock", from_block.to_string()))
+        .and(query_param("page", page_number.to_string()))
+        .respond_with(response)
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn full_pages_read_the_next_page() {
+    let (server, client) = stand_in().await;
+    serve_page(&server, 0, 1, page(16, LOGS_PAGE_SIZE)).await;
+    serve_page(&server, 0, 2, page(16, 3)).await;
+
+    let transfers = client.transfers(ICAP, 0, None).await.unwrap();
+    assert_eq!(transfers.len(), LOGS_PAGE_SIZE + 3);
+    assert_eq!(TokenFlows::from_transfers(&transfers).minted, (LOGS_PAGE_SIZE + 3) as u128);
+}
+
+#[tokio::test]
+async fn result_limit_continues_from_the_last_block() {
+    let (server, client) = stand_in().await;
+    let pages = LOGS_RESULT_LIMIT / LOGS_PAGE_SIZE;
+    for page_number in 1..pages {
+        serve_page(&server, 0, page_number, page(16, LOGS_PAGE_SIZE)).await;
+    }
+    // the last block may go on past the limit, it's read again from its first log
+    serve_page(&server, 0, pages, page(32, LOGS_PAGE_SIZE)).await;
+    serve_page(&server, 32, 1, page(32, LOGS_PAGE_SIZE)).await;
+    serve_page(&server, 32, 2, page(33, 3)).await;
+
+    let transfers = client.transfers(ICAP, 0, None).await.unwrap();
+    assert_eq!(transfers.len(), LOGS_RESULT_LIMIT + 3);
+    assert_eq!(transfers.iter().filter(|transfer| transfer.block == 32).count(), LOGS_PAGE_SIZE);
+    assert!(transfers.windows(2).all(|pair| pair[0].block <= pair[1].block));
+}
+
+#[tokio::test]
+async fn block_over_the_result_limit_is_reported() {
+    let (server, client) = stand_in().await;
+    Mock::given(method("GET")).respond_with(page(16, LOGS_PAGE_SIZE)).mount(&server).await;
+
+    assert!(matches!(client.transfers(ICAP, 0, None).await, Err(EtherscanError::LogLimit(16))));
+    let from_blocks: Vec<String> = server.received_requests().await.unwrap().iter()
+        .filter_map(|request| request.url.query_pairs().find(|(name, _)| name == "fromBlock").map(|(_, block)| block.into_owned()))
+        .collect();
+    // one full query from the start and one from the block, which can't move on
+    assert_eq!(from_blocks.iter().filter(|block| *block == "0").count(), LOGS_RESULT_LIMIT / LOGS_PAGE_SIZE);
+    assert_eq!(from_blocks.iter().filter(|block| *block == "16").count(), LOGS_RESULT_LIMIT / LOGS_PAGE_SIZE);
+}
+
+#[tokio::test]
+async fn malformed_log_invalid() {
+    let (server, client) = stand_in().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "status": "1",
+            "message": "OK",
+            "result": [{ "topics": [TRANSFER_TOPIC], "data": "0x", "blockNumber": "0x1", "timeStamp": "0x1", "transactionHash": "0xaa" }],
+        })))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(
+        client.transfers(ICAP, 0, None).await,
+        Err(EtherscanError::InvalidResult { action: "getLogs", .. })
+    ));
+}
@@ -60,10 +60,11 @@ impl EventHandler for Handler {
         // An AtomicBool is used because it doesn't require a mutable reference to be changed, as
         // we don't have one due to self being an immutable reference.
         if !self.is_loop_running.load(Ordering::Relaxed) {
-            let ( fund_ticker, update_frequency, playing) = {
+            let ( fund_ticker, update_frequency, playing, token, quote) = {
                 let data_read_lock = ctx.data.read().await;
                 let config = data_read_lock.get::<utils::Config>().expect("Expected Config in TypeMap.");
-                (config.fund_ticker.clone(), config.update_frequency.clone(), config.playing.clone())
+                let token = config.token_address.clone();
+                (config.fund_ticker.clone(), config.update_frequency, config.playing, token, config.quote.clone())
     
             };
             // let (fund_ticker_clone, update_frequency_clone) = (fund_ticker.clone(), update_frequency.clone());
@@ -76,7 +77,7 @@ impl EventHandler for Handler {
 
             let ctx2 = Arc::clone(&ctx);
             tokio::spawn(async move {
-                utils::update_nick_and_activity(Arc::clone(&ctx2), &fund_ticker, guilds, update_frequency, &playing).await;
+                utils::update_nick_and_activity(Arc::clone(&ctx2), &token, &quote, &fund_ticker, guilds, update_frequency, playing).await;
             });
 
             // Now that the loop is running, we set the bool to true
@@ -90,6 +91,11 @@ impl EventHandler for Handler {
 async fn main() {
     let config: utils::Config = utils::loadconfig().expect("Can't load config file: botconfig.toml. Please make sure you have one next to the executable and it's correct.");
     info!("Botconfig loaded {:?}", &config);
+    if !config.api.init_request_policy() {
+        error!("The request policy was already in use, the request_policy settings in botconfig.toml are ignored.");
+    }
+    let uniswap_client = config.api.uniswap_client().expect("Can't create the uniswap client, please check the api settings in botconfig.toml.");
+    let dex_client = config.api.dex_client(config.dex, &uniswap_client).expect("Can't create the uniswap v3 client, please check the api settings in botconfig.toml.");
 
     let filter = EnvFilter::from_default_env()
         .add_directive(LevelFilter::INFO.into());// Set the base level when not matched by other directives to INFO.
@@ -132,6 +138,7 @@ async fn main() {
         let mut data = client.data.write().await;
         data.insert::<ShardManagerContainer>(client.shard_manager.clone());
         data.insert::<utils::Config>(config);
+        data.insert::<utils::DexContainer>(dex_client);
     }
 
     let shard_manager = client.shard_manager.clone();
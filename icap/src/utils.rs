@@ -1,4 +1,3 @@
-use serde:: Serialize;
 use serde_derive::Deserialize;
 use anyhow::Result;
 use serenity::{
@@ -11,41 +10,38 @@ use std::{
     sync::{Arc},
 };
 
+use api_time::TimeRange;
+use bot_support::ApiConfig;
+use uniswap_v2_api as uniswap;
 use tracing::{info, /*debug*/};
 use tokio::time::{sleep, Duration};
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Post {
-    id: Option<i32>,
-    title: String,
-    body: String,
-    user_id: i32,
-}
-
-pub async fn update_nick_and_activity(ctx: Arc<Context>, fund_ticker: &str, guilds: Vec<GuildId>, update_frequency: u64, playing: &str) {
+pub async fn update_nick_and_activity(ctx: Arc<Context>, token: &str, quote: &uniswap::Quote, fund_ticker: &str, guilds: Vec<GuildId>, update_frequency: u64, playing: TimeRange) {
     // let fund_name = normalize_fund_name(fund_ticker).unwrap_or("NaN".into());
+    let dex = dex(&ctx).await;
+    let twap = twap_activity(&ctx).await;
     loop {
-        let perf_percent = "~";
-        // let perf_percent = match fund_perf(&fund_ticker, "24h").await {
-        //     Ok(percent) => percent,
-        //     Err(e) => {
-        //         info!("fund_perf {} api call failed\n{}", &fund_ticker, e.to_string());
-        //         // sleep(Duration::from_secs(update_frequency)).await;
-        //         // continue;
-        //         "NaN".into()
-        //     },
-        // };
+        let mut perf_percent = String::from("~");
+        if twap {
+            match dex.twap_perf(token, quote, playing).await {
+                Ok(percent) => perf_percent = format!("{:.2}", percent),
+                Err(e) => info!("twap_perf {} uniswap api call failed\n{}", &fund_ticker, e.to_string()),
+            }
+        }
         let trend = if perf_percent.contains("-") {"⬂"} else {"⬀"};
             
-        let mut fund_nav = match uniswap_icap().await {
-            Ok(nav) => nav,
+        let fund_nav = match quote {
+            uniswap::Quote::Usd => dex.usd_price(token).await,
+            uniswap::Quote::Token(_) => dex.price(token, quote).await,
+        };
+        let fund_nav = match fund_nav {
+            Ok(nav) => format!("{:.3}", nav),
             Err(e) => {
-                info!("uniswap {} api call failed\n{}", &fund_ticker, e.to_string());
+                info!("{} {} api call failed\n{}", dex.version(), &fund_ticker, e.to_string());
                 sleep(Duration::from_secs(update_frequency)).await;
                 continue;
             },
         };
-        fund_nav.truncate(fund_nav.find(".").unwrap_or(1) + 4);
         for server in guilds.clone() {
             if let Err(e) = server.edit_nickname(&ctx.http, Some(&format!("{} ${} {}",fund_ticker, fund_nav, trend))).await {
                 info!("{} failed to update nick with nav\n{}", &fund_ticker, e);
@@ -67,9 +63,18 @@ pub struct Config {
     pub fund_ticker: String,
     pub bot_token: String,
     pub update_frequency: u64,
-    pub playing: String,
+    pub playing: TimeRange,
     pub prefix: String,
-    pub allowed_channels: Vec<ChannelId>
+    pub allowed_channels: Vec<ChannelId>,
+    pub token_address: String,
+    #[serde(default)]
+    pub quote: uniswap::Quote,
+    #[serde(default)]
+    pub dex: uniswap::DexVersion,
+    #[serde(default)]
+    pub twap_activity: bool,
+    #[serde(flatten)]
+    pub api: ApiConfig,
 }
 
 // Loading bot config file.
@@ -79,24 +84,20 @@ pub fn loadconfig() -> Result<Config> {
     Ok(config)
 }
 
-pub async fn uniswap_icap() -> Result<String> {
-    
-    let uniswap_url = "https://api.thegraph.com/subgraphs/name/uniswap/uniswap-v2";
+pub struct DexContainer;
+
+impl TypeMapKey for DexContainer {
+    type Value = Arc<dyn uniswap::Dex>;
+}
 
-    let uniswap_json: serde_json::Value = reqwest::Client::new()
-        .post(uniswap_url)
-        .json(&serde_json::json!({
-            "operationName":"NavPrice",
-            "variables":{"token_address":"0xd83c569268930fadad4cde6d0cb64450fef32b65","dai_token_address":"0x6b175474e89094c44da98b954eedeac495271d0f"},
-                "query":"query NavPrice($token_address: String!, $dai_token_address: String!) {\n  pairs(where: {token0: $dai_token_address, token1: $token_address}) {\n    token0Price\n    __typename\n  }\n}\n"
-                }))
-        .send()
-        .await?
-        .json()
-        .await?;
-    // info!("{}", uniswap_json["data"]["pairs"][0]["token0Price"]);
-    let mut icap_price = format!("{}",uniswap_json["data"]["pairs"][0]["token0Price"]);
-    icap_price = icap_price.replace("\"", "");
+// Shared price source from the bot data.
+pub async fn dex(ctx: &Context) -> Arc<dyn uniswap::Dex> {
+    let data_read_lock = ctx.data.read().await;
+    Arc::clone(data_read_lock.get::<DexContainer>().expect("Expected Dex in TypeMap."))
+}
 
-    Ok(icap_price)
+// Whether the activity shows the time weighted average price performance instead of the spot price one.
+pub async fn twap_activity(ctx: &Context) -> bool {
+    let data_read_lock = ctx.data.read().await;
+    data_read_lock.get::<Config>().expect("Expected Config in TypeMap.").twap_activity
 }
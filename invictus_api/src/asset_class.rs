@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use serde_derive::Deserialize;
+
+use crate::{Decimal, FundPieAsset};
+
+/// Class of the stablecoins and fiat, folded into the `USD` row of a [`crate::FundPie`].
+pub const CASH_CLASS: &str = "Cash";
+/// Class of the tickers not listed in any class.
+pub const CRYPTO_CLASS: &str = "Crypto";
+
+/// Ticker to asset class map, `[asset_classes]` table in the bot config keyed by class name.
+/// ```toml
+/// [asset_classes]
+/// Cash = ["USD", "BUSD", "USDC"]
+/// Gold = ["PAXG", "XAUT"]
+/// ```
+/// Setting the table replaces the default classes, unlisted tickers fall in [`CRYPTO_CLASS`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "HashMap<String, Vec<String>>")]
+pub struct AssetClasses {
+    classes: HashMap<String, String>,
+}
+
+impl Default for AssetClasses {
+    fn default() -> Self {
+        let mut classes = HashMap::new();
+        classes.insert(CASH_CLASS.to_string(), to_strings(&["USD", "BUSD", "BUSD-T", "USDC", "USDT", "DAI", "TUSD", "PAX", "USDP", "GUSD"]));
+        classes.insert("Gold".to_string(), to_strings(&["PAXG", "XAUT", "DGX"]));
+        classes.insert("Bitcoin".to_string(), to_strings(&["BTC", "WBTC", "RENBTC", "BTCB", "HBTC"]));
+        Self::from(classes)
+    }
+}
+
+impl From<HashMap<String, Vec<String>>> for AssetClasses {
+    fn from(classes: HashMap<String, Vec<String>>) -> Self {
+        let classes = classes.into_iter()
+            .flat_map(|(class, tickers)| tickers.into_iter().map(move |ticker| (ticker.trim().to_uppercase(), class.clone())))
+            .collect();
+        Self { classes }
+    }
+}
+
+/// Summed value and allocation of the assets in one class.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassTotal {
+    pub class: String,
+    pub value: Decimal,
+    pub percentage: Decimal,
+    pub assets: usize,
+}
+
+impl AssetClasses {
+    pub fn class_of(&self, ticker: &str) -> &str {
+        self.classes.get(&ticker.to_uppercase()).map_or(CRYPTO_CLASS, String::as_str)
+    }
+
+    pub fn is_cash(&self, ticker: &str) -> bool {
+        self.class_of(ticker) == CASH_CLASS
+    }
+
+    /// Per class totals of the assets, largest value first.
+    pub fn group(&self, assets: &[FundPieAsset]) -> Vec<ClassTotal> {
+        let mut totals: Vec<ClassTotal> = vec![];
+        for asset in assets.iter().filter(|asset| !asset.value.is_zero() || !asset.percentage.is_zero()) {
+            let class = self.class_of(&asset.ticker);
+            match totals.iter_mut().find(|total| total.class == class) {
+                Some(total) => {
+                    total.value += asset.value;
+                    total.percentage += asset.percentage;
+                    total.assets += 1;
+                },
+                None => totals.push(ClassTotal {
+                    class: class.to_string(),
+                    value: asset.value,
+                    percentage: asset.percentage,
+                    assets: 1,
+                }),
+            }
+        }
+        totals.sort_by(|a, b| b.value.cmp(&a.value).then_with(|| a.class.cmp(&b.class)));
+        totals
+    }
+
+    /// Summed allocation of the assets in `class`, zero if the fund holds none.
+    pub fn percentage_of(&self, assets: &[FundPieAsset], class: &str) -> Decimal {
+        assets.iter()
+            .filter(|asset| self.class_of(&asset.ticker) == class)
+            .map(|asset| asset.percentage)
+            .sum()
+    }
+}
+
+fn to_strings(tickers: &[&str]) -> Vec<String> {
+    tickers.iter().map(|ticker| ticker.to_string()).collect()
+}
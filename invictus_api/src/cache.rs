@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use serde_derive::Deserialize;
+
+use crate::Result;
+
+/// Api endpoints with their own cache ttl.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    Funds,
+    Pie,
+    Nav,
+    Movement,
+}
+
+/// Cache ttl per endpoint in seconds, `0` disables caching for the endpoint.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct CacheTtl {
+    pub funds: u64,
+    pub pie: u64,
+    pub nav: u64,
+    pub movement: u64,
+}
+
+impl Default for CacheTtl {
+    fn default() -> Self {
+        Self {
+            funds: 30,
+            pie: 60,
+            nav: 30,
+            movement: 60,
+        }
+    }
+}
+
+impl CacheTtl {
+    /// Ttl with caching disabled for every endpoint.
+    pub fn disabled() -> Self {
+        Self {
+            funds: 0,
+            pie: 0,
+            nav: 0,
+            movement: 0,
+        }
+    }
+
+    pub fn of(&self, endpoint: Endpoint) -> Duration {
+        let secs = match endpoint {
+            Endpoint::Funds => self.funds,
+            Endpoint::Pie => self.pie,
+            Endpoint::Nav => self.nav,
+            Endpoint::Movement => self.movement,
+        };
+        Duration::from_secs(secs)
+    }
+}
+
+#[derive(Debug)]
+struct CachedBody {
+    expires_at: Instant,
+    body: Bytes,
+}
+
+type Slot = Arc<tokio::sync::Mutex<Option<CachedBody>>>;
+
+/// Response body cache keyed by request path.
+///
+/// Every key has its own async lock, held while the upstream request is in flight,
+/// so concurrent calls for the same path wait for the first one and share its response.
+/// Expired responses are dropped when a new path is cached.
+#[derive(Debug, Default)]
+pub struct ResponseCache {
+    ttl: CacheTtl,
+    slots: Mutex<HashMap<String, Slot>>,
+}
+
+impl ResponseCache {
+    pub fn new(ttl: CacheTtl) -> Self {
+        Self {
+            ttl,
+            slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn ttl(&self) -> CacheTtl {
+        self.ttl
+    }
+
+    /// Cached body for `key` if it's younger than the endpoint ttl, otherwise the result of `fetch`.
+    /// Failed fetches are not cached.
+    pub async fn get_or_fetch<F, Fut>(&self, endpoint: Endpoint, key: &str, fetch: F) -> Result<Bytes>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Bytes>>,
+    {
+        let ttl = self.ttl.of(endpoint);
+        if ttl.is_zero() {
+            return fetch().await
+        }
+
+        let slot = {
+            let mut slots = self.slots.lock().expect("response cache lock poisoned");
+            if !slots.contains_key(key) {
+                let now = Instant::now();
+                slots.retain(|_, slot| !evictable(slot, now));
+            }
+            Arc::clone(slots.entry(key.to_string()).or_default())
+        };
+        let mut cached = slot.lock().await;
+        if let Some(entry) = cached.as_ref() {
+            if Instant::now() < entry.expires_at {
+                return Ok(entry.body.clone())
+            }
+        }
+        let body = fetch().await?;
+        *cached = Some(CachedBody {
+            expires_at: Instant::now() + ttl,
+            body: body.clone(),
+        });
+        Ok(body)
+    }
+
+    /// Paths with a cached response or a request in flight.
+    pub fn len(&self) -> usize {
+        self.slots.lock().expect("response cache lock poisoned").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops every cached response.
+    pub fn clear(&self) {
+        self.slots.lock().expect("response cache lock poisoned").clear();
+    }
+}
+
+// A slot no call holds, with an expired response or none after a failed fetch.
+fn evictable(slot: &Slot, now: Instant) -> bool {
+    if Arc::strong_count(slot) > 1 {
+        return false
+    }
+    match slot.try_lock() {
+        Ok(cached) => cached.as_ref().is_none_or(|entry| entry.expires_at <= now),
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,198 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use api_policy::RequestPolicy;
+use api_time::{system_clock, Clock, TimeRange};
+use bytes::Bytes;
+use serde::de::DeserializeOwned;
+use serde_derive::Deserialize;
+
+use crate::{ApiFundsGeneral, CacheTtl, Decimal, Endpoint, FundAliases, FundId, FundNav, FundPerf, FundPie, FundRegistry, InvictusError, ResponseCache, Result};
+
+/// Every api response carries a `status` field next to the payload.
+#[derive(Debug, Deserialize)]
+struct StatusEnvelope {
+    status: Option<String>,
+}
+
+pub const INVICTUS_API: &str = "https://api.invictuscapital.com/v2";
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+pub const DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Invictus api client, holds one reusable connection pool and response cache for every endpoint call.
+/// Cloning is cheap, clones share the same connection pool and cache.
+#[derive(Debug, Clone)]
+pub struct InvictusClient {
+    http: reqwest::Client,
+    base_url: String,
+    cache: Arc<ResponseCache>,
+    policy: Arc<RequestPolicy>,
+    clock: Arc<dyn Clock>,
+}
+
+impl InvictusClient {
+    /// Client with the default base url, timeout and user agent.
+    pub fn new() -> Result<Self> {
+        Self::builder().build()
+    }
+
+    pub fn builder() -> InvictusClientBuilder {
+        InvictusClientBuilder::default()
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn cache(&self) -> &ResponseCache {
+        &self.cache
+    }
+
+    /// Clock the year to date range ends at.
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// General information of all the funds, `/funds` endpoint.
+    pub async fn funds(&self) -> Result<ApiFundsGeneral> {
+        self.get(Endpoint::Funds, "funds").await
+    }
+
+    /// Fund registry from the `/funds` endpoint, extended with the given aliases.
+    pub async fn fund_registry(&self, aliases: &FundAliases) -> Result<FundRegistry> {
+        let funds_general_raw = self.funds().await?.data;
+        Ok(FundRegistry::from_funds(&funds_general_raw).with_aliases(aliases))
+    }
+
+    /// Nav per token of the given fund.
+    pub async fn nav_per_token(&self, fund: &FundId) -> Result<Decimal> {
+        let funds_general_raw = self.funds().await?.data;
+        for fund_general in funds_general_raw {
+            if fund_general.name == fund.as_str() {
+                return Ok(fund_general.nav_per_token)
+            }
+        }
+        Err(InvictusError::UnknownFund(fund.to_string()))
+    }
+
+    /// Asset allocation of the given fund, `/funds/{fund}/pie` endpoint.
+    pub async fn fund_pie(&self, fund: &FundId) -> Result<FundPie> {
+        self.get(Endpoint::Pie, &format!("funds/{}/pie", fund)).await
+    }
+
+    /// Net asset value details of the given fund, `/funds/{fund}/nav` endpoint.
+    pub async fn fund_nav(&self, fund: &FundId) -> Result<FundNav> {
+        self.get(Endpoint::Nav, &format!("funds/{}/nav", fund)).await
+    }
+
+    /// Performance percentage of the given fund over `range`, `/funds/{fund}/movement` endpoint.
+    /// Fails with [`InvictusError::RangeNotServed`] for a range the endpoint can't read.
+    pub async fn fund_perf(&self, fund: &FundId, range: TimeRange) -> Result<Decimal> {
+        let range_parameter = range.invictus_range_at(self.clock.now()).ok_or(InvictusError::RangeNotServed(range))?;
+        let fund_performance: FundPerf = self.get(Endpoint::Movement, &format!("funds/{}/movement?range={}", fund, range_parameter)).await?;
+        Ok(fund_performance.percentage)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, endpoint: Endpoint, path: &str) -> Result<T> {
+        let body = self.cache.get_or_fetch(endpoint, path, || self.fetch(path)).await?;
+        serde_json::from_slice(&body).map_err(|source| InvictusError::Decode { endpoint: path.to_string(), source })
+    }
+
+    /// Response body of a successful request, after the `status` field check.
+    async fn fetch(&self, path: &str) -> Result<Bytes> {
+        let request = self.http
+            .get(format!("{}/{}", self.base_url, path))
+            .build()?;
+        let response = self.policy.execute(&self.http, request).await?;
+        let http_status = response.status();
+        let body = response.bytes().await?;
+
+        let envelope = match serde_json::from_slice::<StatusEnvelope>(&body) {
+            Ok(envelope) => envelope,
+            Err(_) if !http_status.is_success() => return Err(InvictusError::HttpStatus(http_status.as_u16())),
+            Err(source) => return Err(InvictusError::Decode { endpoint: path.to_string(), source }),
+        };
+        match envelope.status.as_deref() {
+            Some("success") => {},
+            Some(status) => return Err(InvictusError::ApiStatus(status.to_string())),
+            None if !http_status.is_success() => return Err(InvictusError::HttpStatus(http_status.as_u16())),
+            None => return Err(InvictusError::MissingField("status")),
+        }
+        Ok(body)
+    }
+}
+
+/// Builder for [`InvictusClient`], every option falls back to a sane default.
+#[derive(Debug, Clone)]
+pub struct InvictusClientBuilder {
+    base_url: String,
+    timeout: Duration,
+    user_agent: String,
+    cache_ttl: CacheTtl,
+    policy: Option<Arc<RequestPolicy>>,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl Default for InvictusClientBuilder {
+    fn default() -> Self {
+        Self {
+            base_url: INVICTUS_API.into(),
+            timeout: DEFAULT_TIMEOUT,
+            user_agent: DEFAULT_USER_AGENT.into(),
+            cache_ttl: CacheTtl::default(),
+            policy: None,
+            clock: None,
+        }
+    }
+}
+
+impl InvictusClientBuilder {
+    /// Api base url, eg.: `http://127.0.0.1:8080/v2` for a local mock server.
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').into();
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Response cache ttl per endpoint, [`CacheTtl::disabled`] turns caching off.
+    pub fn cache_ttl(mut self, cache_ttl: CacheTtl) -> Self {
+        self.cache_ttl = cache_ttl;
+        self
+    }
+
+    /// Retry and rate limit policy, the process wide [`RequestPolicy::global`] if not set.
+    pub fn request_policy(mut self, policy: Arc<RequestPolicy>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Clock the year to date range ends at, the system clock if not set.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    pub fn build(self) -> Result<InvictusClient> {
+        let http = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .connect_timeout(self.timeout)
+            .user_agent(self.user_agent)
+            .build()?;
+        Ok(InvictusClient {
+            http,
+            base_url: self.base_url,
+            cache: Arc::new(ResponseCache::new(self.cache_ttl)),
+            policy: self.policy.unwrap_or_else(RequestPolicy::global),
+            clock: self.clock.unwrap_or_else(system_clock),
+        })
+    }
+}
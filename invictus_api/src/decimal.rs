@@ -0,0 +1,22 @@
+use rust_decimal::prelude::ToPrimitive;
+
+pub use rust_decimal::Decimal;
+
+/// Display helpers for the decimal values in the api responses.
+pub trait Precision {
+    /// Value truncated (not rounded) to `dp` decimal places, zero padded, eg.: `1.23456` -> `1.234`.
+    fn with_precision(&self, dp: u32) -> String;
+
+    /// Whole units, the fraction is truncated. Saturates on values outside of the `i64` range.
+    fn whole(&self) -> i64;
+}
+
+impl Precision for Decimal {
+    fn with_precision(&self, dp: u32) -> String {
+        format!("{:.*}", dp as usize, self.trunc_with_scale(dp))
+    }
+
+    fn whole(&self) -> i64 {
+        self.trunc().to_i64().unwrap_or(if self.is_sign_negative() { i64::MIN } else { i64::MAX })
+    }
+}
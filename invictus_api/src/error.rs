@@ -0,0 +1,35 @@
+use api_time::TimeRange;
+use thiserror::Error;
+
+pub type Result<T, E = InvictusError> = std::result::Result<T, E>;
+
+#[derive(Debug, Error)]
+pub enum InvictusError {
+    /// The fund is not listed by the api or not known by the registry.
+    #[error("unknown fund: {0}")]
+    UnknownFund(String),
+    /// Connection, timeout or other transport level failure.
+    #[error("invictus api request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// Non success http status without a parsable api response.
+    #[error("invictus api responded with http status {0}")]
+    HttpStatus(u16),
+    /// The response body doesn't match the expected schema.
+    #[error("failed to decode the invictus api response from {endpoint}: {source}")]
+    Decode {
+        endpoint: String,
+        source: serde_json::Error,
+    },
+    /// The api responded with a `status` other than `success`.
+    #[error("invictus api responded with status: {0}")]
+    ApiStatus(String),
+    /// A field required to answer the request is missing from the response.
+    #[error("invictus api response is missing the `{0}` field")]
+    MissingField(&'static str),
+    /// The movement endpoint reads up to 48 hours or whole weeks, other lengths are rejected before the request.
+    #[error("time range `{0}` can't be read from the invictus api, use up to 2d or whole weeks, eg.: 7d or 1w")]
+    RangeNotServed(TimeRange),
+    /// The fund aliases file can't be read or parsed.
+    #[error("failed to load fund aliases: {0}")]
+    Aliases(String),
+}
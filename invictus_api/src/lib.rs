@@ -1,5 +1,17 @@
 use serde_derive::{Serialize, Deserialize};
-use anyhow::{Result, anyhow};
+
+mod error;
+pub use error::{InvictusError, Result};
+mod cache;
+pub use cache::{CacheTtl, Endpoint, ResponseCache};
+mod client;
+pub use client::{InvictusClient, InvictusClientBuilder, INVICTUS_API, DEFAULT_TIMEOUT, DEFAULT_USER_AGENT};
+mod decimal;
+pub use decimal::{Decimal, Precision};
+mod registry;
+pub use registry::{FundAliases, FundId, FundRegistry};
+mod asset_class;
+pub use asset_class::{AssetClasses, ClassTotal, CASH_CLASS, CRYPTO_CLASS};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ApiFundsGeneral {
@@ -9,35 +21,13 @@ pub struct ApiFundsGeneral {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct FundGeneral {
-    pub circulating_supply: String,
-    pub net_asset_value: String,
-    pub nav_per_token: String,
+    pub circulating_supply: Decimal,
+    pub net_asset_value: Decimal,
+    pub nav_per_token: Decimal,
     pub name: String,
     pub ticker: String
 }
 
-pub async fn api_general() -> Result<ApiFundsGeneral> {
-    let api_response = reqwest::get("https://api.invictuscapital.com/v2/funds")
-        .await?
-        .json::<ApiFundsGeneral>()
-        .await?;
-    Ok(api_response)
-}
-
-pub async fn fund_nav(fund_name: &str) -> Result<String> {
-    let fund_to_check = normalize_fund_name(fund_name)?;
-    let api_response = api_general().await?;
-    let funds_general_raw = api_response.data;
-    for fund in funds_general_raw {
-        if fund.name == fund_to_check {
-            let mut nav = fund.nav_per_token;
-            nav.truncate(nav.find(".").unwrap() + 4);
-            return Ok(nav)
-        }
-    }
-    Ok("notfound".into())
-}
-
 #[derive(Debug, Deserialize, Serialize)]
 pub struct FundPie {
     status: String,
@@ -47,63 +37,77 @@ pub struct FundPie {
 pub struct FundPieAsset {
     pub ticker: String,
     pub name: String,
-    pub value: String,
-    pub amount: String,
-    pub price: String,
-    pub percentage: String
+    pub value: Decimal,
+    pub amount: Decimal,
+    pub price: Decimal,
+    pub percentage: Decimal
 }
 
+/// Allocation in percent at or below which [`FundPie::remove_small_assets`] moves an asset into the other row.
+pub const SMALL_ASSET_THRESHOLD: Decimal = Decimal::ONE;
+
 impl FundPie {
-    pub async fn fund_pie(fund_name: &str) -> Result<FundPie> {
-        let fund_to_check = normalize_fund_name(fund_name)?;
-        let pie = reqwest::get(format!("https://api.invictuscapital.com/v2/funds/{}/pie", fund_to_check))
-            .await?
-            .json::<FundPie>()
-            .await?;
-        
-        Ok(pie)
-    }
-    
-    pub fn remove_small_assets(&mut self) {
-        self.stablecoin_summary();
-        self.assets.retain(|asset| (asset.percentage.parse::<f64>().unwrap()) > 1.0);
+    /// Folds the stablecoins, then sums the assets at or below `threshold` percent into an `Other (N assets)` row.
+    pub fn remove_small_assets(&mut self, classes: &AssetClasses, threshold: Decimal) {
+        self.stablecoin_summary(classes);
+        self.assets.retain(|asset| !asset.percentage.is_zero() || !asset.value.is_zero());
+        let (assets, small_assets): (Vec<_>, Vec<_>) = self.assets.drain(..).partition(|asset| asset.percentage > threshold);
+        self.assets = assets;
+        if small_assets.is_empty() {
+            return
+        }
+        let other_value: Decimal = small_assets.iter().map(|asset| asset.value).sum();
+        let other_percentage: Decimal = small_assets.iter().map(|asset| asset.percentage).sum();
+        self.assets.push(FundPieAsset {
+            ticker: format!("Other ({} asset{})", small_assets.len(), if small_assets.len() == 1 { "" } else { "s" }),
+            name: "Other".into(),
+            value: other_value,
+            amount: Decimal::ZERO,
+            price: Decimal::ZERO,
+            percentage: other_percentage.trunc_with_scale(2),
+        });
     }
 
-    pub fn stablecoin_summary(&mut self) {
-        let mut usd_percentage = 0.0;
-        let mut usd_value = 0.0;
-        for asset in self.assets.iter_mut() {
-            match asset.ticker.as_ref() {
-                "USD" | "BUSD" | "BUSD-T" => {
-                    usd_percentage += asset.percentage.parse::<f64>().unwrap();
-                    usd_value += asset.value.parse::<f64>().unwrap();
-                    asset.percentage = "0.0".to_string();
-                    asset.value = "0.0".to_string();
-                }
-                &_ => {}
-            }
+    /// Folds every asset of the cash class into the `USD` row, the first cash asset becomes the row if the fund holds no USD.
+    pub fn stablecoin_summary(&mut self, classes: &AssetClasses) {
+        let summary_row = match self.assets.iter().position(|asset| asset.ticker == "USD")
+            .or_else(|| self.assets.iter().position(|asset| classes.is_cash(&asset.ticker))) {
+            Some(row) => row,
+            None => return,
+        };
+        let mut usd_percentage = Decimal::ZERO;
+        let mut usd_value = Decimal::ZERO;
+        for asset in self.assets.iter_mut().filter(|asset| classes.is_cash(&asset.ticker)) {
+            usd_percentage += asset.percentage;
+            usd_value += asset.value;
+            asset.percentage = Decimal::ZERO;
+            asset.value = Decimal::ZERO;
         }
-        for asset in self.assets.iter_mut() {
-            match asset.ticker.as_ref() {
-                "USD" => {
-                    asset.percentage = usd_percentage.to_string();
-                    asset.percentage.truncate(asset.percentage.find(".").unwrap_or(1) + 3);
-                    asset.value = usd_value.to_string();
-                }
-                &_ => {}
-            }
+        let usd = &mut self.assets[summary_row];
+        if usd.ticker != "USD" {
+            usd.ticker = "USD".into();
+            usd.name = "US Dollar".into();
+            usd.price = Decimal::ONE;
         }
+        usd.percentage = usd_percentage.trunc_with_scale(2);
+        usd.value = usd_value;
+        usd.amount = usd_value;
+    }
+
+    /// Per asset class totals of the fund, largest value first.
+    pub fn grouped_by_class(&self, classes: &AssetClasses) -> Vec<ClassTotal> {
+        classes.group(&self.assets)
     }
 }
 #[derive(Debug, Deserialize, Serialize)]
 pub struct FundNav {
     status: String,
-    circulating_supply: String,
-    net_asset_value: String,
-    nav_per_token: String,
+    circulating_supply: Decimal,
+    net_asset_value: Decimal,
+    nav_per_token: Decimal,
     name: String,
     ticker: String,
-    price: String,
+    price: Decimal,
     pub assets: Vec<FundNavAsset>
 }
 
@@ -111,50 +115,30 @@ pub struct FundNav {
 pub struct FundNavAsset {
     pub name: String,
     pub ticker: String,
-    pub usd_value: String
+    pub usd_value: Decimal
 }
 
 impl FundNav {
-    pub async fn fund_nav(fund_name: &str) -> Result<FundNav> {
-        let fund_to_check = normalize_fund_name(fund_name)?;
-        let pie = reqwest::get(format!("https://api.invictuscapital.com/v2/funds/{}/nav", fund_to_check))
-            .await?
-            .json::<FundNav>()
-            .await?;
-        
-        Ok(pie)
+    pub fn net_asset_value(&self) -> Decimal {
+        self.net_asset_value
+    }
+
+    pub fn nav_per_token(&self) -> Decimal {
+        self.nav_per_token
+    }
+
+    pub fn circulating_supply(&self) -> Decimal {
+        self.circulating_supply
     }
 
-    pub fn net_asset_value(&self) -> String {
-        self.net_asset_value.clone()
+    pub fn price(&self) -> Decimal {
+        self.price
     }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct FundPerf {
     status: String,
-    pub percentage: String
-}
-
-pub async fn fund_perf(fund_name: &str, range: &str) -> Result<String> {
-    let fund_to_check = normalize_fund_name(fund_name)?;
-    let fund_performance = reqwest::get(format!("https://api.invictuscapital.com/v2/funds/{}/movement?range={}", fund_to_check, range))
-        .await?
-        .json::<FundPerf>()
-        .await?;
-    Ok(fund_performance.percentage)
+    pub percentage: Decimal
 }
 
-
-pub fn normalize_fund_name(got_name: &str) -> Result<String> {
-    match got_name {
-        "c20" | "crypto20" | "C20" => Ok("crypto20".into()),
-        "c10" | "crypto10" | "C10" => Ok("crypto10".into()),
-        "iba" | "bitcoin-alpha" | "IBA" => Ok("bitcoin-alpha".into()),
-        "ihf" | "hyperion" | "IHF" => Ok("hyperion".into()),
-        "iml" | "margin-lending" | "IML" => Ok("margin-lending".into()),
-        "igp" | "gold-plus" | "IGP" => Ok("gold-plus".into()),
-        "ems" | "emerging-markets-solar" | "EMS" => Ok("emerging-markets-solar".into()),
-        &_ => return Err(anyhow!("notfound"))
-    }
-}
\ No newline at end of file
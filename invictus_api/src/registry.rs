@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use serde_derive::Deserialize;
+
+use crate::{FundGeneral, InvictusError, Result};
+
+/// Fund identifier as used in the api paths, eg.: `crypto20`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FundId(String);
+
+impl FundId {
+    pub fn new(name: &str) -> Self {
+        Self(name.to_lowercase())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for FundId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Extra fund aliases loaded from a toml file, keyed by fund name or ticker.
+/// ```toml
+/// [aliases]
+/// crypto20 = ["crypto 20", "top20"]
+/// IHF = ["hyperion-fund"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FundAliases {
+    #[serde(default)]
+    pub aliases: HashMap<String, Vec<String>>,
+}
+
+impl FundAliases {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let aliases_toml = std::fs::read_to_string(path).map_err(|e| InvictusError::Aliases(e.to_string()))?;
+        toml::from_str(&aliases_toml).map_err(|e| InvictusError::Aliases(e.to_string()))
+    }
+}
+
+/// Case insensitive lookup table from fund names, tickers and aliases to [`FundId`].
+#[derive(Debug, Clone, Default)]
+pub struct FundRegistry {
+    lookup: HashMap<String, FundId>,
+    tickers: HashMap<FundId, String>,
+}
+
+impl FundRegistry {
+    /// Registry of the funds listed in the `/funds` endpoint response.
+    pub fn from_funds(funds: &[FundGeneral]) -> Self {
+        let mut registry = Self::default();
+        for fund in funds {
+            registry.insert(&fund.name, &fund.ticker);
+        }
+        registry
+    }
+
+    /// Registry of the funds known at the time of writing, used until the first successful api refresh.
+    pub fn builtin() -> Self {
+        let mut registry = Self::default();
+        for (name, ticker) in &[
+            ("crypto20", "C20"),
+            ("crypto10", "C10"),
+            ("bitcoin-alpha", "IBA"),
+            ("hyperion", "IHF"),
+            ("margin-lending", "IML"),
+            ("gold-plus", "IGP"),
+            ("emerging-markets-solar", "EMS"),
+        ] {
+            registry.insert(name, ticker);
+        }
+        registry
+    }
+
+    /// Adds the aliases to the registry, aliases for unknown funds are skipped.
+    pub fn with_aliases(mut self, aliases: &FundAliases) -> Self {
+        for (fund, fund_aliases) in &aliases.aliases {
+            if let Some(fund_id) = self.lookup(fund) {
+                for alias in fund_aliases {
+                    self.lookup.insert(alias.trim().to_lowercase(), fund_id.clone());
+                }
+            }
+        }
+        self
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<FundId> {
+        self.lookup.get(&name.trim().to_lowercase()).cloned()
+    }
+
+    pub fn ticker(&self, fund_id: &FundId) -> Option<&str> {
+        self.tickers.get(fund_id).map(String::as_str)
+    }
+
+    pub fn funds(&self) -> impl Iterator<Item = &FundId> {
+        self.tickers.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tickers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tickers.is_empty()
+    }
+
+    fn insert(&mut self, name: &str, ticker: &str) {
+        let fund_id = FundId::new(name);
+        self.lookup.insert(name.to_lowercase(), fund_id.clone());
+        self.lookup.insert(ticker.to_lowercase(), fund_id.clone());
+        self.tickers.insert(fund_id, ticker.to_uppercase());
+    }
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use common::{serve, stand_in};
+use invictus_api::{AssetClasses, Decimal, FundId, CASH_CLASS, CRYPTO_CLASS, SMALL_ASSET_THRESHOLD};
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+#[tokio::test]
+async fn pie_grouped_by_class() {
+    let (server, client) = stand_in().await;
+    serve(&server, "funds/crypto10/pie", 200, "pie_classes.json").await;
+
+    let pie = client.fund_pie(&FundId::new("crypto10")).await.unwrap();
+    let totals = pie.grouped_by_class(&AssetClasses::default());
+    let classes: Vec<(&str, usize)> = totals.iter().map(|total| (total.class.as_str(), total.assets)).collect();
+    assert_eq!(classes, vec![("Bitcoin", 1), (CRYPTO_CLASS, 2), (CASH_CLASS, 2), ("Gold", 1)]);
+    assert_eq!(totals[1].percentage, dec("31"));
+    assert_eq!(totals[2].value, dec("2000000"));
+    assert_eq!(totals[2].percentage, dec("20"));
+}
+
+#[tokio::test]
+async fn stablecoins_without_usd_row_are_folded() {
+    let (server, client) = stand_in().await;
+    serve(&server, "funds/crypto10/pie", 200, "pie_classes.json").await;
+
+    let classes = AssetClasses::default();
+    let mut pie = client.fund_pie(&FundId::new("crypto10")).await.unwrap();
+    pie.remove_small_assets(&classes, SMALL_ASSET_THRESHOLD);
+    let tickers: Vec<&str> = pie.assets.iter().map(|asset| asset.ticker.as_str()).collect();
+    assert_eq!(tickers, vec!["WBTC", "ETH", "USD", "PAXG", "Other (1 asset)"]);
+    assert_eq!(pie.assets[2].value, dec("2000000"));
+    assert_eq!(pie.assets[2].percentage, dec("20"));
+
+    let totals = pie.grouped_by_class(&classes);
+    assert_eq!(totals.iter().find(|total| total.class == CASH_CLASS).unwrap().assets, 1);
+}
+
+#[test]
+fn configured_classes_replace_the_defaults() {
+    let mut configured = HashMap::new();
+    configured.insert(CASH_CLASS.to_string(), vec!["usdc".to_string()]);
+    configured.insert("Gold".to_string(), vec![" paxg ".to_string()]);
+    let classes = AssetClasses::from(configured);
+
+    assert!(classes.is_cash("USDC"));
+    assert!(!classes.is_cash("DAI"));
+    assert_eq!(classes.class_of("PAXG"), "Gold");
+    assert_eq!(classes.class_of("WBTC"), CRYPTO_CLASS);
+}
+
+#[test]
+fn classes_from_toml() {
+    let classes: AssetClasses = toml::from_str("Cash = [\"USD\", \"TUSD\"]\nStaking = [\"DOT\"]").unwrap();
+    assert!(classes.is_cash("tusd"));
+    assert_eq!(classes.class_of("DOT"), "Staking");
+    assert_eq!(classes.class_of("BTC"), CRYPTO_CLASS);
+}
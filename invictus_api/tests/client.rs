@@ -0,0 +1,275 @@
+mod common;
+
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use api_time::{FixedClock, TimeRange};
+use common::{client_with_cache, serve, serve_movement, stand_in};
+use invictus_api::{AssetClasses, CacheTtl, Decimal, Endpoint, FundId, InvictusClient, InvictusError, Precision, ResponseCache, SMALL_ASSET_THRESHOLD};
+use wiremock::{Mock, ResponseTemplate};
+use wiremock::matchers::{method, path};
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+#[tokio::test]
+async fn funds_success() {
+    let (server, client) = stand_in().await;
+    serve(&server, "funds", 200, "funds.json").await;
+
+    let funds = client.funds().await.unwrap();
+    assert_eq!(funds.status, "success");
+    assert_eq!(funds.data.len(), 3);
+    assert_eq!(funds.data[0].name, "crypto10");
+    assert_eq!(funds.data[0].nav_per_token, dec("1.41830512"));
+    assert_eq!(funds.data[1].net_asset_value.whole(), 98_765_432);
+}
+
+#[tokio::test]
+async fn funds_malformed_value_is_a_decode_error() {
+    let (server, client) = stand_in().await;
+    serve(&server, "funds", 200, "funds_malformed.json").await;
+
+    match client.funds().await {
+        Err(InvictusError::Decode { endpoint, .. }) => assert_eq!(endpoint, "funds"),
+        other => panic!("expected decode error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn funds_not_json_is_a_decode_error() {
+    let (server, client) = stand_in().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/funds"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("<html>maintenance</html>"))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(client.funds().await, Err(InvictusError::Decode { .. })));
+}
+
+#[tokio::test]
+async fn api_error_status_is_reported() {
+    let (server, client) = stand_in().await;
+    serve(&server, "funds", 200, "error.json").await;
+
+    match client.funds().await {
+        Err(InvictusError::ApiStatus(status)) => assert_eq!(status, "error"),
+        other => panic!("expected api status error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn api_error_status_wins_over_http_status() {
+    let (server, client) = stand_in().await;
+    serve(&server, "funds/crypto10/pie", 404, "error.json").await;
+
+    assert!(matches!(client.fund_pie(&FundId::new("crypto10")).await, Err(InvictusError::ApiStatus(_))));
+}
+
+#[tokio::test]
+async fn missing_status_field_is_reported() {
+    let (server, client) = stand_in().await;
+    serve_movement(&server, "crypto10", "24h", 200, "missing_status.json").await;
+
+    assert!(matches!(
+        client.fund_perf(&FundId::new("crypto10"), TimeRange::Days(1)).await,
+        Err(InvictusError::MissingField("status"))
+    ));
+}
+
+#[tokio::test]
+async fn http_error_without_api_response() {
+    let (server, client) = stand_in().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/funds"))
+        .respond_with(ResponseTemplate::new(502).set_body_string("Bad Gateway"))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(client.funds().await, Err(InvictusError::HttpStatus(502))));
+}
+
+#[tokio::test]
+async fn unreachable_api_is_an_http_error() {
+    let client = InvictusClient::builder()
+        .base_url("http://127.0.0.1:9/v2")
+        .timeout(Duration::from_secs(2))
+        .request_policy(common::no_retry_policy())
+        .build()
+        .unwrap();
+
+    assert!(matches!(client.funds().await, Err(InvictusError::Http(_))));
+}
+
+#[tokio::test]
+async fn nav_per_token_success() {
+    let (server, client) = stand_in().await;
+    serve(&server, "funds", 200, "funds.json").await;
+
+    let nav = client.nav_per_token(&FundId::new("crypto20")).await.unwrap();
+    assert_eq!(nav.with_precision(3), "2.462");
+}
+
+#[tokio::test]
+async fn nav_per_token_unknown_fund() {
+    let (server, client) = stand_in().await;
+    serve(&server, "funds", 200, "funds.json").await;
+
+    match client.nav_per_token(&FundId::new("gold-plus")).await {
+        Err(InvictusError::UnknownFund(fund)) => assert_eq!(fund, "gold-plus"),
+        other => panic!("expected unknown fund, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn fund_pie_success() {
+    let (server, client) = stand_in().await;
+    serve(&server, "funds/crypto10/pie", 200, "pie.json").await;
+
+    let mut pie = client.fund_pie(&FundId::new("crypto10")).await.unwrap();
+    assert_eq!(pie.assets.len(), 6);
+
+    pie.remove_small_assets(&AssetClasses::default(), SMALL_ASSET_THRESHOLD);
+    let tickers: Vec<&str> = pie.assets.iter().map(|asset| asset.ticker.as_str()).collect();
+    assert_eq!(tickers, vec!["BTC", "ETH", "USD", "ADA", "Other (1 asset)"]);
+    let usd = pie.assets.iter().find(|asset| asset.ticker == "USD").unwrap();
+    assert_eq!(usd.percentage, dec("15.61"));
+    assert_eq!(usd.value, dec("8000000"));
+    assert_eq!(pie.assets[4].percentage, dec("0.47"));
+    assert_eq!(pie.assets[4].value, dec("240000"));
+}
+
+#[tokio::test]
+async fn fund_pie_small_assets_threshold() {
+    let (server, client) = stand_in().await;
+    serve(&server, "funds/crypto10/pie", 200, "pie.json").await;
+
+    let mut pie = client.fund_pie(&FundId::new("crypto10")).await.unwrap();
+    pie.remove_small_assets(&AssetClasses::default(), dec("10"));
+    let tickers: Vec<&str> = pie.assets.iter().map(|asset| asset.ticker.as_str()).collect();
+    assert_eq!(tickers, vec!["BTC", "ETH", "USD", "Other (2 assets)"]);
+    assert_eq!(pie.assets[3].percentage, dec("6.32"));
+    assert_eq!(pie.assets[3].value, dec("3240000"));
+    let total_value: Decimal = pie.assets.iter().map(|asset| asset.value).sum();
+    assert_eq!(total_value, dec("51240000.75"));
+
+    let mut pie = client.fund_pie(&FundId::new("crypto10")).await.unwrap();
+    pie.remove_small_assets(&AssetClasses::default(), Decimal::ZERO);
+    let tickers: Vec<&str> = pie.assets.iter().map(|asset| asset.ticker.as_str()).collect();
+    assert_eq!(tickers, vec!["BTC", "ETH", "USD", "ADA", "DOGE"]);
+}
+
+#[tokio::test]
+async fn fund_pie_malformed() {
+    let (server, client) = stand_in().await;
+    serve(&server, "funds/crypto10/pie", 200, "funds.json").await;
+
+    assert!(matches!(client.fund_pie(&FundId::new("crypto10")).await, Err(InvictusError::Decode { .. })));
+}
+
+#[tokio::test]
+async fn fund_nav_success() {
+    let (server, client) = stand_in().await;
+    serve(&server, "funds/crypto10/nav", 200, "nav.json").await;
+
+    let nav = client.fund_nav(&FundId::new("crypto10")).await.unwrap();
+    assert_eq!(nav.net_asset_value().whole(), 51_234_567);
+    assert_eq!(nav.nav_per_token().with_precision(2), "1.41");
+    assert_eq!(nav.price(), dec("1.4"));
+    assert_eq!(nav.assets[1].usd_value, dec("15000000.25"));
+}
+
+#[tokio::test]
+async fn fund_nav_error() {
+    let (server, client) = stand_in().await;
+    serve(&server, "funds/crypto10/nav", 500, "error.json").await;
+
+    assert!(matches!(client.fund_nav(&FundId::new("crypto10")).await, Err(InvictusError::ApiStatus(_))));
+}
+
+#[tokio::test]
+async fn fund_perf_success() {
+    let (server, client) = stand_in().await;
+    serve_movement(&server, "crypto10", "1w", 200, "movement.json").await;
+
+    let perf = client.fund_perf(&FundId::new("crypto10"), TimeRange::Weeks(1)).await.unwrap();
+    assert_eq!(perf.with_precision(2), "-3.14");
+}
+
+#[tokio::test]
+async fn perf_of_a_range_the_api_cant_read_is_not_requested() {
+    let (server, client) = stand_in().await;
+
+    let perf = client.fund_perf(&FundId::new("crypto10"), TimeRange::Days(3)).await;
+    assert!(matches!(perf, Err(InvictusError::RangeNotServed(TimeRange::Days(3)))));
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn year_to_date_perf_ends_at_the_clock_time() {
+    let server = wiremock::MockServer::start().await;
+    serve_movement(&server, "crypto10", "11w", 200, "movement.json").await;
+    // 2021-03-15 12:00:00 UTC
+    let client = InvictusClient::builder()
+        .base_url(&format!("{}/v2", server.uri()))
+        .cache_ttl(CacheTtl::disabled())
+        .request_policy(common::no_retry_policy())
+        .clock(Arc::new(FixedClock::new(1_615_809_600)))
+        .build()
+        .unwrap();
+
+    let perf = client.fund_perf(&FundId::new("crypto10"), TimeRange::YearToDate).await.unwrap();
+    assert_eq!(perf.with_precision(2), "-3.14");
+}
+
+#[tokio::test]
+async fn concurrent_requests_share_one_upstream_call() {
+    let server = wiremock::MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/funds"))
+        .respond_with(common::FIXTURES.json_response(200, "funds.json").set_delay(Duration::from_millis(200)))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = client_with_cache(&server, CacheTtl::default());
+
+    let c10 = FundId::new("crypto10");
+    let (funds, nav, again) = tokio::join!(client.funds(), client.nav_per_token(&c10), client.funds());
+    assert_eq!(funds.unwrap().data.len(), 3);
+    assert_eq!(nav.unwrap(), dec("1.41830512"));
+    assert_eq!(again.unwrap().data.len(), 3);
+}
+
+#[tokio::test]
+async fn failed_requests_are_not_cached() {
+    let server = wiremock::MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/funds"))
+        .respond_with(common::FIXTURES.json_response(200, "error.json"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    serve(&server, "funds", 200, "funds.json").await;
+    let client = client_with_cache(&server, CacheTtl::default());
+
+    assert!(client.funds().await.is_err());
+    assert!(client.funds().await.is_ok());
+}
+
+#[tokio::test]
+async fn expired_responses_are_evicted_when_a_new_path_is_cached() {
+    let cache = ResponseCache::new(CacheTtl { funds: 1, ..CacheTtl::default() });
+    cache.get_or_fetch(Endpoint::Funds, "funds", || async { Ok(Default::default()) }).await.unwrap();
+    cache.get_or_fetch(Endpoint::Pie, "funds/crypto10/pie", || async { Ok(Default::default()) }).await.unwrap();
+    let failed = cache.get_or_fetch(Endpoint::Nav, "funds/crypto10/nav", || async { Err(InvictusError::HttpStatus(502)) }).await;
+    assert!(failed.is_err());
+    assert_eq!(cache.len(), 3);
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    // the funds response expired and the nav fetch failed, the pie response is still fresh
+    cache.get_or_fetch(Endpoint::Movement, "funds/crypto10/movement?range=1w", || async { Ok(Default::default()) }).await.unwrap();
+    assert_eq!(cache.len(), 2);
+}
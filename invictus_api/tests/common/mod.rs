@@ -0,0 +1,40 @@
+#![allow(dead_code)]
+
+pub use test_support::no_retry_policy;
+use test_support::Fixtures;
+use invictus_api::{CacheTtl, InvictusClient};
+use wiremock::{Mock, MockServer};
+use wiremock::matchers::{method, path, query_param};
+
+pub const FIXTURES: Fixtures = Fixtures::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures"));
+
+/// Local stand-in for the invictus api and a client pointed at it, caching disabled.
+pub async fn stand_in() -> (MockServer, InvictusClient) {
+    test_support::stand_in(|server| client_with_cache(server, CacheTtl::disabled())).await
+}
+
+pub fn client_with_cache(server: &MockServer, cache_ttl: CacheTtl) -> InvictusClient {
+    InvictusClient::builder()
+        .base_url(&format!("{}/v2", server.uri()))
+        .cache_ttl(cache_ttl)
+        .request_policy(no_retry_policy())
+        .build()
+        .expect("client")
+}
+
+pub async fn serve(server: &MockServer, endpoint: &str, status: u16, fixture_name: &str) {
+    Mock::given(method("GET"))
+        .and(path(format!("/v2/{}", endpoint)))
+        .respond_with(FIXTURES.json_response(status, fixture_name))
+        .mount(server)
+        .await;
+}
+
+pub async fn serve_movement(server: &MockServer, fund: &str, range: &str, status: u16, fixture_name: &str) {
+    Mock::given(method("GET"))
+        .and(path(format!("/v2/funds/{}/movement", fund)))
+        .and(query_param("range", range))
+        .respond_with(FIXTURES.json_response(status, fixture_name))
+        .mount(server)
+        .await;
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use common::{serve, stand_in, FIXTURES};
+use invictus_api::{FundAliases, FundId, FundRegistry, InvictusError};
+
+fn aliases() -> FundAliases {
+    let aliases_path = format!("{}/tests/fixtures/aliases.toml", env!("CARGO_MANIFEST_DIR"));
+    FundAliases::load(aliases_path).unwrap()
+}
+
+#[tokio::test]
+async fn registry_from_the_funds_endpoint() {
+    let (server, client) = stand_in().await;
+    serve(&server, "funds", 200, "funds.json").await;
+
+    let registry = client.fund_registry(&aliases()).await.unwrap();
+    assert_eq!(registry.len(), 3);
+    assert_eq!(registry.lookup("Crypto20"), Some(FundId::new("crypto20")));
+    assert_eq!(registry.lookup("c20"), Some(FundId::new("crypto20")));
+    assert_eq!(registry.lookup(" TOP20 "), Some(FundId::new("crypto20")));
+    assert_eq!(registry.lookup("Hyperion-Fund"), Some(FundId::new("hyperion")));
+    assert_eq!(registry.lookup("nothing"), None);
+    assert_eq!(registry.lookup("iml"), None);
+    assert_eq!(registry.ticker(&FundId::new("hyperion")), Some("IHF"));
+}
+
+#[tokio::test]
+async fn registry_refresh_error() {
+    let (server, client) = stand_in().await;
+    serve(&server, "funds", 200, "error.json").await;
+
+    assert!(matches!(client.fund_registry(&FundAliases::default()).await, Err(InvictusError::ApiStatus(_))));
+}
+
+#[test]
+fn builtin_registry_knows_the_original_funds() {
+    let registry = FundRegistry::builtin();
+    assert_eq!(registry.len(), 7);
+    assert_eq!(registry.lookup("IML"), Some(FundId::new("margin-lending")));
+    assert_eq!(registry.lookup("emerging-markets-solar"), Some(FundId::new("emerging-markets-solar")));
+}
+
+#[test]
+fn malformed_aliases_file() {
+    let aliases_path = std::env::temp_dir().join(format!("invictus_api_aliases_{}.toml", std::process::id()));
+    std::fs::write(&aliases_path, FIXTURES.read("funds.json")).unwrap();
+    let loaded = FundAliases::load(&aliases_path);
+    std::fs::remove_file(&aliases_path).unwrap();
+
+    assert!(matches!(loaded, Err(InvictusError::Aliases(_))));
+}
+
+#[test]
+fn missing_aliases_file() {
+    assert!(matches!(FundAliases::load("does-not-exist.toml"), Err(InvictusError::Aliases(_))));
+}
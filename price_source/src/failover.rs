@@ -0,0 +1,137 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use anyhow::{Error, Result};
+use api_time::TimeRange;
+use async_trait::async_trait;
+use invictus_api::{Decimal, Precision};
+
+use crate::{Backend, PriceSource};
+
+/// Percent the navs of a fund's sources may differ by before the bots warn about them, eg.: a stale api.
+pub const DIVERGENCE_THRESHOLD: Decimal = Decimal::from_parts(5, 0, 0, false, 0);
+
+type SourceCall<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Ordered price sources of a fund, each call is answered by the first source that doesn't fail.
+#[derive(Debug, Clone)]
+pub struct FailoverSource {
+    sources: Vec<Arc<dyn PriceSource>>,
+}
+
+impl FailoverSource {
+    /// # Panics
+    /// On an empty source list.
+    pub fn new(sources: Vec<Arc<dyn PriceSource>>) -> Self {
+        assert!(!sources.is_empty(), "a failover source needs at least one price source");
+        FailoverSource { sources }
+    }
+
+    pub fn sources(&self) -> &[Arc<dyn PriceSource>] {
+        &self.sources
+    }
+
+    /// Nav of every source, in the order of the chain.
+    pub async fn nav_readings(&self) -> NavReadings {
+        let mut readings = Vec::with_capacity(self.sources.len());
+        for source in &self.sources {
+            readings.push((source.backend(), source.nav().await));
+        }
+        NavReadings { readings }
+    }
+
+    // The error of the last source when all of them fail.
+    async fn first_ok<'a, T>(&'a self, call: impl Fn(&'a Arc<dyn PriceSource>) -> SourceCall<'a, T> + Send + Sync + 'a) -> Result<T> {
+        let mut last_error = None;
+        for source in &self.sources {
+            match call(source).await {
+                Ok(value) => return Ok(value),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.expect("failover sources are never empty"))
+    }
+}
+
+#[async_trait]
+impl PriceSource for FailoverSource {
+    /// Backend of the primary source.
+    fn backend(&self) -> Backend {
+        self.sources[0].backend()
+    }
+
+    async fn nav(&self) -> Result<Decimal> {
+        self.first_ok(|source| source.nav()).await
+    }
+
+    async fn perf(&self, range: TimeRange) -> Result<Decimal> {
+        self.first_ok(move |source| source.perf(range)).await
+    }
+
+    async fn perf_ranges(&self, ranges: &[TimeRange]) -> Result<Vec<Decimal>> {
+        self.first_ok(|source| source.perf_ranges(ranges)).await
+    }
+
+    async fn twap_perf(&self, range: TimeRange) -> Result<Decimal> {
+        self.first_ok(move |source| source.twap_perf(range)).await
+    }
+
+    async fn net_value(&self) -> Result<Decimal> {
+        self.first_ok(|source| source.net_value()).await
+    }
+}
+
+/// Navs read from each source of a fund, to cross-check them.
+#[derive(Debug)]
+pub struct NavReadings {
+    readings: Vec<(Backend, Result<Decimal>)>,
+}
+
+impl NavReadings {
+    /// Nav of the first source that answered.
+    pub fn nav(&self) -> Option<Decimal> {
+        self.readings.iter().find_map(|(_, nav)| nav.as_ref().ok().copied())
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = (Backend, &Error)> {
+        self.readings.iter().filter_map(|(backend, nav)| nav.as_ref().err().map(|e| (*backend, e)))
+    }
+
+    /// Difference between the highest and the lowest nav in percent of the lowest,
+    /// `None` with less than two readings to compare.
+    pub fn spread(&self) -> Option<Decimal> {
+        let navs: Vec<Decimal> = self.readings.iter().filter_map(|(_, nav)| nav.as_ref().ok().copied()).collect();
+        if navs.len() < 2 {
+            return None
+        }
+        let lowest = navs.iter().min()?;
+        let highest = navs.iter().max()?;
+        if lowest.is_sign_negative() || lowest.is_zero() {
+            return None
+        }
+        Some((highest - lowest) / lowest * Decimal::ONE_HUNDRED)
+    }
+
+    /// Whether the navs differ by more than `threshold` percent.
+    pub fn diverge(&self, threshold: Decimal) -> bool {
+        self.spread().is_some_and(|spread| spread > threshold)
+    }
+}
+
+/// Each source with its nav, eg.: `uniswap $4.066, invictus failed`.
+impl fmt::Display for NavReadings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (backend, nav)) in self.readings.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            match nav {
+                Ok(nav) => write!(f, "{} ${}", backend, nav.with_precision(3))?,
+                Err(_) => write!(f, "{} failed", backend)?,
+            }
+        }
+        Ok(())
+    }
+}
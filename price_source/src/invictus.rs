@@ -0,0 +1,42 @@
+use anyhow::Result;
+use api_time::TimeRange;
+use async_trait::async_trait;
+use invictus_api::{Decimal, FundId, InvictusClient};
+
+use crate::{Backend, PriceSource};
+
+/// Fund values reported by the invictus api.
+#[derive(Debug, Clone)]
+pub struct InvictusSource {
+    client: InvictusClient,
+    fund: FundId,
+}
+
+impl InvictusSource {
+    pub fn new(client: InvictusClient, fund: FundId) -> Self {
+        InvictusSource { client, fund }
+    }
+
+    pub fn fund(&self) -> &FundId {
+        &self.fund
+    }
+}
+
+#[async_trait]
+impl PriceSource for InvictusSource {
+    fn backend(&self) -> Backend {
+        Backend::Invictus
+    }
+
+    async fn nav(&self) -> Result<Decimal> {
+        Ok(self.client.nav_per_token(&self.fund).await?)
+    }
+
+    async fn perf(&self, range: TimeRange) -> Result<Decimal> {
+        Ok(self.client.fund_perf(&self.fund, range).await?)
+    }
+
+    async fn net_value(&self) -> Result<Decimal> {
+        Ok(self.client.fund_nav(&self.fund).await?.net_asset_value())
+    }
+}
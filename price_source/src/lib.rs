@@ -0,0 +1,88 @@
+use std::fmt;
+
+use anyhow::Result;
+use api_time::TimeRange;
+use async_trait::async_trait;
+use invictus_api::Decimal;
+use serde_derive::Deserialize;
+use thiserror::Error;
+
+mod invictus;
+pub use invictus::InvictusSource;
+mod uniswap;
+pub use uniswap::UniswapSource;
+mod rpc;
+pub use rpc::RpcSource;
+mod failover;
+pub use failover::{FailoverSource, NavReadings, DIVERGENCE_THRESHOLD};
+mod sources;
+pub use sources::{PriceSources, SourceChain, SourceConfig};
+
+/// Backend a fund's price source reads from, `invictus`, `uniswap` or `rpc` in the bot config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    Invictus,
+    Uniswap,
+    /// Uniswap V2 pair reserves read from an ethereum node.
+    Rpc,
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backend::Invictus => f.write_str("invictus"),
+            Backend::Uniswap => f.write_str("uniswap"),
+            Backend::Rpc => f.write_str("rpc"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PriceSourceError {
+    #[error("{backend} price source has no {what}")]
+    Unsupported { backend: Backend, what: &'static str },
+    #[error("price source of {0} has no token address set")]
+    MissingToken(String),
+    #[error("rpc price source of {0} has no pair address set")]
+    MissingPair(String),
+    #[error("price source list of {0} is empty")]
+    EmptyChain(String),
+    #[error("{0} is not a finite value")]
+    NotFinite(f64),
+}
+
+/// Nav and performance of a fund or token, so the bots don't branch on the fund to pick an api.
+#[async_trait]
+pub trait PriceSource: fmt::Debug + Send + Sync {
+    fn backend(&self) -> Backend;
+
+    /// Latest value of one token.
+    async fn nav(&self) -> Result<Decimal>;
+
+    /// Value change over the range in percent.
+    async fn perf(&self, range: TimeRange) -> Result<Decimal>;
+
+    /// Value change over each range in percent, in the order of the ranges.
+    async fn perf_ranges(&self, ranges: &[TimeRange]) -> Result<Vec<Decimal>> {
+        let mut perf = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            perf.push(self.perf(*range).await?);
+        }
+        Ok(perf)
+    }
+
+    /// Time weighted value change over the range in percent, the spot value change where the backend has no time weighted prices.
+    async fn twap_perf(&self, range: TimeRange) -> Result<Decimal> {
+        self.perf(range).await
+    }
+
+    /// Total USD value held by the fund.
+    async fn net_value(&self) -> Result<Decimal>;
+}
+
+// Subgraph prices are floats, the bots format decimals.
+pub(crate) fn decimal(value: f64) -> Result<Decimal> {
+    use rust_decimal::prelude::FromPrimitive;
+    Ok(Decimal::from_f64(value).ok_or(PriceSourceError::NotFinite(value))?)
+}
@@ -0,0 +1,49 @@
+use anyhow::Result;
+use api_time::TimeRange;
+use async_trait::async_trait;
+use eth_rpc::{BlockId, EthRpcClient};
+use invictus_api::Decimal;
+
+use crate::{decimal, Backend, PriceSource, PriceSourceError};
+
+/// Token price of a Uniswap V2 pair read from an ethereum node, without a subgraph or Etherscan.
+/// The price is in the other token of the pair, a stablecoin pair gives USD prices.
+#[derive(Debug, Clone)]
+pub struct RpcSource {
+    rpc: EthRpcClient,
+    pair: String,
+    token: String,
+}
+
+impl RpcSource {
+    pub fn new(rpc: EthRpcClient, pair: &str, token: &str) -> Self {
+        RpcSource { rpc, pair: pair.to_lowercase(), token: token.to_lowercase() }
+    }
+
+    pub fn pair(&self) -> &str {
+        &self.pair
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+#[async_trait]
+impl PriceSource for RpcSource {
+    fn backend(&self) -> Backend {
+        Backend::Rpc
+    }
+
+    async fn nav(&self) -> Result<Decimal> {
+        decimal(self.rpc.pair_price(&self.pair, &self.token, BlockId::Latest).await?)
+    }
+
+    async fn perf(&self, range: TimeRange) -> Result<Decimal> {
+        decimal(self.rpc.pair_perf(&self.pair, &self.token, range).await?)
+    }
+
+    async fn net_value(&self) -> Result<Decimal> {
+        Err(PriceSourceError::Unsupported { backend: Backend::Rpc, what: "fund net value" }.into())
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use eth_rpc::EthRpcClient;
+use invictus_api::{FundId, FundRegistry, InvictusClient};
+use serde_derive::Deserialize;
+use uniswap_v2_api::{Dex, DexVersion, Quote};
+
+use crate::{Backend, FailoverSource, InvictusSource, PriceSource, PriceSourceError, RpcSource, UniswapSource};
+
+/// Price source of a fund in the bot config, eg.: `[price_sources.icap]` with `source = "uniswap"`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SourceConfig {
+    pub source: Backend,
+    /// Token address of a uniswap or rpc source.
+    pub token: Option<String>,
+    /// Uniswap V2 pair address of an rpc source, the token is priced in the other token of the pair.
+    pub pair: Option<String>,
+    #[serde(default)]
+    pub quote: Quote,
+    #[serde(default)]
+    pub dex: DexVersion,
+}
+
+impl SourceConfig {
+    /// Uniswap V2 USD price of the token.
+    pub fn uniswap(token: &str) -> Self {
+        SourceConfig { source: Backend::Uniswap, token: Some(token.to_string()), pair: None, quote: Quote::Usd, dex: DexVersion::V2 }
+    }
+}
+
+/// Price sources of a fund in failover order, one `[price_sources.c20]` table or a `[[price_sources.icap]]` list in the bot config.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(from = "OneOrMany")]
+pub struct SourceChain(Vec<SourceConfig>);
+
+impl SourceChain {
+    pub fn new(sources: Vec<SourceConfig>) -> Self {
+        SourceChain(sources)
+    }
+
+    pub fn sources(&self) -> &[SourceConfig] {
+        &self.0
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(SourceConfig),
+    Many(Vec<SourceConfig>),
+}
+
+impl From<OneOrMany> for SourceChain {
+    fn from(sources: OneOrMany) -> Self {
+        match sources {
+            OneOrMany::One(source) => SourceChain(vec![source]),
+            OneOrMany::Many(sources) => SourceChain(sources),
+        }
+    }
+}
+
+/// Picks the price source of a fund by name from the config entries, the invictus api for the rest.
+#[derive(Debug, Clone)]
+pub struct PriceSources {
+    entries: HashMap<String, SourceChain>,
+    invictus: InvictusClient,
+    uniswap_v2: Arc<dyn Dex>,
+    uniswap_v3: Arc<dyn Dex>,
+    rpc: EthRpcClient,
+}
+
+impl PriceSources {
+    /// Sources of the configured entries, funds without an entry read the invictus api. Fund names are case insensitive.
+    pub fn new(entries: &HashMap<String, SourceChain>, invictus: InvictusClient, uniswap_v2: Arc<dyn Dex>, uniswap_v3: Arc<dyn Dex>, rpc: EthRpcClient) -> Result<Self, PriceSourceError> {
+        let mut all_entries = HashMap::new();
+        for (name, chain) in entries {
+            if chain.0.is_empty() {
+                return Err(PriceSourceError::EmptyChain(name.clone()));
+            }
+            if chain.0.iter().any(|entry| entry.source != Backend::Invictus && entry.token.is_none()) {
+                return Err(PriceSourceError::MissingToken(name.clone()));
+            }
+            if chain.0.iter().any(|entry| entry.source == Backend::Rpc && entry.pair.is_none()) {
+                return Err(PriceSourceError::MissingPair(name.clone()));
+            }
+            all_entries.insert(name.to_lowercase(), chain.clone());
+        }
+        Ok(PriceSources { entries: all_entries, invictus, uniswap_v2, uniswap_v3, rpc })
+    }
+
+    /// Config entry of the fund, keyed by `name` itself or by any name, ticker or alias the registry resolves to the same fund,
+    /// so `[price_sources.c20]` also prices `crypto20`. Of several entries of one fund the first key in order wins.
+    pub fn entry(&self, name: &str, registry: &FundRegistry) -> Option<&SourceChain> {
+        if let Some(chain) = self.entries.get(&name.trim().to_lowercase()) {
+            return Some(chain)
+        }
+        let fund = registry.lookup(name)?;
+        self.entries.iter()
+            .filter(|(key, _)| registry.lookup(key).as_ref() == Some(&fund))
+            .min_by_key(|(key, _)| key.as_str())
+            .map(|(_, chain)| chain)
+    }
+
+    /// Price source of the fund, the registry resolves `name` to the fund of the config entry and of the invictus backend.
+    pub fn source(&self, name: &str, registry: &FundRegistry) -> Option<Arc<dyn PriceSource>> {
+        let chain = self.chain(name, registry)?;
+        match chain.sources() {
+            [source] => Some(Arc::clone(source)),
+            _ => Some(Arc::new(chain)),
+        }
+    }
+
+    /// Price sources of the fund in failover order, invictus entries are left out without a registry fund.
+    pub fn chain(&self, name: &str, registry: &FundRegistry) -> Option<FailoverSource> {
+        let fund = registry.lookup(name);
+        let sources: Vec<Arc<dyn PriceSource>> = match self.entry(name, registry) {
+            Some(chain) => chain.0.iter().filter_map(|entry| self.build(entry, fund.as_ref())).collect(),
+            None => fund.map(|fund| Arc::new(InvictusSource::new(self.invictus.clone(), fund)) as Arc<dyn PriceSource>).into_iter().collect(),
+        };
+        if sources.is_empty() {
+            return None
+        }
+        Some(FailoverSource::new(sources))
+    }
+
+    fn build(&self, entry: &SourceConfig, fund: Option<&FundId>) -> Option<Arc<dyn PriceSource>> {
+        match entry {
+            SourceConfig { source: Backend::Uniswap, token: Some(token), quote, dex, .. } => {
+                let dex = match dex {
+                    DexVersion::V2 => Arc::clone(&self.uniswap_v2),
+                    DexVersion::V3 => Arc::clone(&self.uniswap_v3),
+                };
+                Some(Arc::new(UniswapSource::new(dex, token, quote.clone())))
+            },
+            SourceConfig { source: Backend::Rpc, token: Some(token), pair: Some(pair), .. } => {
+                Some(Arc::new(RpcSource::new(self.rpc.clone(), pair, token)))
+            },
+            _ => fund.map(|fund| Arc::new(InvictusSource::new(self.invictus.clone(), fund.clone())) as Arc<dyn PriceSource>),
+        }
+    }
+}
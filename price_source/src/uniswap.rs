@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use api_time::TimeRange;
+use async_trait::async_trait;
+use invictus_api::Decimal;
+use uniswap_v2_api::{Dex, Quote};
+
+use crate::{decimal, Backend, PriceSource, PriceSourceError};
+
+/// Token price of a Uniswap subgraph, for funds traded as a token.
+#[derive(Debug, Clone)]
+pub struct UniswapSource {
+    dex: Arc<dyn Dex>,
+    token: String,
+    quote: Quote,
+}
+
+impl UniswapSource {
+    pub fn new(dex: Arc<dyn Dex>, token: &str, quote: Quote) -> Self {
+        UniswapSource { dex, token: token.to_lowercase(), quote }
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+#[async_trait]
+impl PriceSource for UniswapSource {
+    fn backend(&self) -> Backend {
+        Backend::Uniswap
+    }
+
+    /// USD prices are weighted by the liquidity of the token's pools against the quote tokens.
+    async fn nav(&self) -> Result<Decimal> {
+        let price = match &self.quote {
+            Quote::Usd => self.dex.usd_price(&self.token).await?,
+            Quote::Token(_) => self.dex.price(&self.token, &self.quote).await?,
+        };
+        decimal(price)
+    }
+
+    async fn perf(&self, range: TimeRange) -> Result<Decimal> {
+        decimal(self.dex.perf(&self.token, &self.quote, range).await?)
+    }
+
+    async fn perf_ranges(&self, ranges: &[TimeRange]) -> Result<Vec<Decimal>> {
+        self.dex.perf_ranges(&self.token, &self.quote, ranges).await?
+            .into_iter()
+            .map(decimal)
+            .collect()
+    }
+
+    async fn twap_perf(&self, range: TimeRange) -> Result<Decimal> {
+        decimal(self.dex.twap_perf(&self.token, &self.quote, range).await?)
+    }
+
+    async fn net_value(&self) -> Result<Decimal> {
+        Err(PriceSourceError::Unsupported { backend: Backend::Uniswap, what: "fund net value" }.into())
+    }
+}
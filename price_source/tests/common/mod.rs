@@ -0,0 +1,106 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use test_support::{no_retry_policy, Fixtures};
+use eth_rpc::EthRpcClient;
+use invictus_api::{CacheTtl, InvictusClient};
+use price_source::{PriceSources, SourceChain};
+use uniswap_v2_api::{Dex, UniswapV2Client, UniswapV3Client, ICAP};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+use serde_json::json;
+use wiremock::matchers::{body_partial_json, method, path, query_param};
+
+pub const V2_SUBGRAPH_PATH: &str = "/subgraphs/name/uniswap/uniswap-v2";
+pub const V3_SUBGRAPH_PATH: &str = "/subgraphs/name/uniswap/uniswap-v3";
+pub const RPC_PATH: &str = "/rpc";
+/// Uniswap V2 pair of ICAP and a 6 decimals stablecoin on the stand-in node.
+pub const PAIR: &str = "0x00000000000000000000000000000000000000a1";
+pub const STABLECOIN: &str = "0x00000000000000000000000000000000000000bb";
+
+/// Recorded responses of the invictus api, served from the fixtures of its client.
+pub const INVICTUS_FIXTURES: Fixtures = Fixtures::new(concat!(env!("CARGO_MANIFEST_DIR"), "/../invictus_api/tests/fixtures"));
+/// Recorded responses of the uniswap subgraphs, served from the fixtures of their clients.
+pub const UNISWAP_FIXTURES: Fixtures = Fixtures::new(concat!(env!("CARGO_MANIFEST_DIR"), "/../uniswap_v2_api/tests/fixtures"));
+
+/// Local stand-in for the invictus api, both uniswap subgraphs and an ethereum node, with the price sources of the config entries pointed at it.
+pub async fn stand_in(entries: &HashMap<String, SourceChain>) -> (MockServer, PriceSources) {
+    test_support::stand_in(|server| {
+        let (invictus, uniswap_v2, uniswap_v3, rpc) = clients(server);
+        PriceSources::new(entries, invictus, uniswap_v2, uniswap_v3, rpc).unwrap()
+    }).await
+}
+
+pub fn clients(server: &MockServer) -> (InvictusClient, Arc<dyn Dex>, Arc<dyn Dex>, EthRpcClient) {
+    let invictus = InvictusClient::builder()
+        .base_url(&format!("{}/v2", server.uri()))
+        .cache_ttl(CacheTtl::disabled())
+        .request_policy(no_retry_policy())
+        .build()
+        .unwrap();
+    let uniswap_v2 = UniswapV2Client::builder()
+        .subgraph_url(&format!("{}{}", server.uri(), V2_SUBGRAPH_PATH))
+        .request_policy(no_retry_policy())
+        .build()
+        .unwrap();
+    let uniswap_v3 = UniswapV3Client::builder()
+        .subgraph_url(&format!("{}{}", server.uri(), V3_SUBGRAPH_PATH))
+        .request_policy(no_retry_policy())
+        .build()
+        .unwrap();
+    let rpc = EthRpcClient::builder()
+        .url(&format!("{}{}", server.uri(), RPC_PATH))
+        .request_policy(no_retry_policy())
+        .build()
+        .unwrap();
+    (invictus, Arc::new(uniswap_v2), Arc::new(uniswap_v3), rpc)
+}
+
+pub async fn serve(server: &MockServer, endpoint: &str, fixture_name: &str) {
+    Mock::given(method("GET"))
+        .and(path(format!("/v2/{}", endpoint)))
+        .respond_with(INVICTUS_FIXTURES.json_response(200, fixture_name))
+        .mount(server)
+        .await;
+}
+
+pub async fn serve_movement(server: &MockServer, fund: &str, range: &str, fixture_name: &str) {
+    Mock::given(method("GET"))
+        .and(path(format!("/v2/funds/{}/movement", fund)))
+        .and(query_param("range", range))
+        .respond_with(INVICTUS_FIXTURES.json_response(200, fixture_name))
+        .mount(server)
+        .await;
+}
+
+pub async fn serve_subgraph(server: &MockServer, subgraph_path: &str, fixture_name: &str) {
+    Mock::given(method("POST"))
+        .and(path(subgraph_path))
+        .respond_with(UNISWAP_FIXTURES.json_response(200, fixture_name))
+        .mount(server)
+        .await;
+}
+
+/// Serves the `PAIR` at the latest block, 1000 ICAP against 4066 of the stablecoin.
+pub async fn serve_pair(server: &MockServer) {
+    let calls = [
+        (PAIR, "0x0dfe1681", word(&ICAP[2..])),
+        (PAIR, "0xd21220a7", word(&STABLECOIN[2..])),
+        (ICAP, "0x313ce567", word("12")),
+        (STABLECOIN, "0x313ce567", word("6")),
+        (PAIR, "0x0902f1ac", format!("0x{:064x}{:064x}{:064x}", 1000 * 10u128.pow(18), 4066 * 10u128.pow(6), 1_624_971_895)),
+    ];
+    for (to, data, result) in calls.iter() {
+        Mock::given(method("POST"))
+            .and(path(RPC_PATH))
+            .and(body_partial_json(json!({ "method": "eth_call", "params": [{ "to": to, "data": data }, "latest"] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "id": 1, "result": result })))
+            .mount(server)
+            .await;
+    }
+}
+
+fn word(hex: &str) -> String {
+    format!("0x{:0>64}", hex)
+}
@@ -0,0 +1,225 @@
+mod common;
+
+use std::collections::HashMap;
+
+use api_time::TimeRange;
+use common::{clients, serve, serve_movement, serve_pair, serve_subgraph, stand_in, PAIR, V2_SUBGRAPH_PATH, V3_SUBGRAPH_PATH};
+use invictus_api::{FundAliases, FundRegistry, InvictusError, Precision};
+use price_source::{Backend, PriceSourceError, PriceSources, SourceChain, DIVERGENCE_THRESHOLD};
+use uniswap_v2_api::{DexVersion, Quote, ICAP};
+use wiremock::MockServer;
+
+fn entries(config: &str) -> HashMap<String, SourceChain> {
+    toml::from_str(config).unwrap()
+}
+
+fn registry() -> FundRegistry {
+    FundRegistry::builtin()
+}
+
+// Registry where `icap` is an alias of the hyperion fund, for the invictus entries of the icap chain.
+fn hyperion_as_icap() -> FundRegistry {
+    let mut aliases = FundAliases::default();
+    aliases.aliases.insert("hyperion".into(), vec!["icap".into()]);
+    FundRegistry::builtin().with_aliases(&aliases)
+}
+
+#[test]
+fn source_config_from_toml() {
+    let entries = entries(r#"
+        [c20]
+        source = "invictus"
+
+        [icap]
+        source = "uniswap"
+        token = "0xd83c569268930fadad4cde6d0cb64450fef32b65"
+        quote = "0x6B175474E89094C44DA98B954EEDEAC495271D0F"
+        dex = "v3"
+    "#);
+
+    assert_eq!(entries["c20"].sources()[0].source, Backend::Invictus);
+    let icap = &entries["icap"].sources()[0];
+    assert_eq!(icap.source, Backend::Uniswap);
+    assert_eq!(icap.quote, Quote::Token("0x6b175474e89094c44da98b954eedeac495271d0f".into()));
+    assert_eq!(icap.dex, DexVersion::V3);
+}
+
+#[test]
+fn source_chain_from_toml() {
+    let entries = entries(r#"
+        [[icap]]
+        source = "uniswap"
+        token = "0xd83c569268930fadad4cde6d0cb64450fef32b65"
+
+        [[icap]]
+        source = "invictus"
+    "#);
+
+    let backends: Vec<Backend> = entries["icap"].sources().iter().map(|entry| entry.source).collect();
+    assert_eq!(backends, vec![Backend::Uniswap, Backend::Invictus]);
+}
+
+#[tokio::test]
+async fn uniswap_entry_needs_a_token() {
+    let server = MockServer::start().await;
+    let (invictus, uniswap_v2, uniswap_v3, rpc) = clients(&server);
+
+    let error = PriceSources::new(&entries("[icap]\nsource = \"uniswap\""), invictus, uniswap_v2, uniswap_v3, rpc).unwrap_err();
+    assert!(matches!(error, PriceSourceError::MissingToken(name) if name == "icap"));
+}
+
+#[tokio::test]
+async fn source_chain_cant_be_empty() {
+    let server = MockServer::start().await;
+    let (invictus, uniswap_v2, uniswap_v3, rpc) = clients(&server);
+
+    let error = PriceSources::new(&entries("icap = []"), invictus, uniswap_v2, uniswap_v3, rpc).unwrap_err();
+    assert!(matches!(error, PriceSourceError::EmptyChain(name) if name == "icap"));
+}
+
+#[tokio::test]
+async fn rpc_entry_needs_a_pair() {
+    let server = MockServer::start().await;
+    let (invictus, uniswap_v2, uniswap_v3, rpc) = clients(&server);
+
+    let error = PriceSources::new(&entries(&format!("[icap]\nsource = \"rpc\"\ntoken = \"{}\"", ICAP)), invictus, uniswap_v2, uniswap_v3, rpc).unwrap_err();
+    assert!(matches!(error, PriceSourceError::MissingPair(name) if name == "icap"));
+}
+
+#[tokio::test]
+async fn rpc_reads_the_pair_reserves() {
+    let (server, sources) = stand_in(&entries(&format!("[icap]\nsource = \"rpc\"\ntoken = \"{}\"\npair = \"{}\"", ICAP, PAIR))).await;
+    serve_pair(&server).await;
+
+    let source = sources.source("icap", &registry()).unwrap();
+    assert_eq!(source.backend(), Backend::Rpc);
+    assert_eq!(source.nav().await.unwrap().with_precision(3), "4.066");
+    assert!(matches!(
+        source.net_value().await.unwrap_err().downcast::<PriceSourceError>(),
+        Ok(PriceSourceError::Unsupported { backend: Backend::Rpc, .. })
+    ));
+}
+
+#[tokio::test]
+async fn invictus_is_the_default_backend() {
+    let (server, sources) = stand_in(&HashMap::new()).await;
+    serve(&server, "funds", "funds.json").await;
+    serve(&server, "funds/crypto10/nav", "nav.json").await;
+    serve_movement(&server, "crypto10", "1w", "movement.json").await;
+
+    let source = sources.source("C10", &registry()).unwrap();
+    assert_eq!(source.backend(), Backend::Invictus);
+    assert_eq!(source.nav().await.unwrap().with_precision(3), "1.418");
+    assert_eq!(source.perf(TimeRange::Weeks(1)).await.unwrap().with_precision(2), "-3.14");
+    assert_eq!(source.net_value().await.unwrap().whole(), 51_234_567);
+}
+
+#[tokio::test]
+async fn unknown_fund_has_no_source() {
+    let (_server, sources) = stand_in(&HashMap::new()).await;
+
+    assert!(sources.source("nope", &registry()).is_none());
+    // icap isn't an invictus fund, without an entry it has no source
+    assert!(sources.source("icap", &registry()).is_none());
+}
+
+#[tokio::test]
+async fn uniswap_entry_reads_the_token_pairs() {
+    let (server, sources) = stand_in(&entries(&format!("[icap]\nsource = \"uniswap\"\ntoken = \"{}\"", ICAP))).await;
+    serve_subgraph(&server, V2_SUBGRAPH_PATH, "token_pairs.json").await;
+
+    let source = sources.source("ICAP", &registry()).unwrap();
+    assert_eq!(source.backend(), Backend::Uniswap);
+    // DAI pair at $4.0 with $100k and WETH pair at $4.2 with $50k, the $500 USDT pair is under the floor
+    assert_eq!(source.nav().await.unwrap().with_precision(3), "4.066");
+    assert!(matches!(
+        source.net_value().await.unwrap_err().downcast_ref::<PriceSourceError>(),
+        Some(PriceSourceError::Unsupported { backend: Backend::Uniswap, .. })
+    ));
+}
+
+#[tokio::test]
+async fn v3_entry_reads_the_pools() {
+    let (server, sources) = stand_in(&entries(&format!("[ICAP]\nsource = \"uniswap\"\ntoken = \"{}\"\ndex = \"v3\"", ICAP))).await;
+    serve_subgraph(&server, V3_SUBGRAPH_PATH, "v3_pools.json").await;
+
+    // USDC pool at $4 with $300k, USDT pool at $4.5 and WETH pool at $5 with $100k each
+    let source = sources.source("icap", &registry()).unwrap();
+    assert_eq!(source.nav().await.unwrap().with_precision(3), "4.300");
+}
+
+#[tokio::test]
+async fn invictus_entry_uses_the_registry_fund() {
+    let (server, sources) = stand_in(&entries("[icap]\nsource = \"invictus\"")).await;
+    serve(&server, "funds", "funds.json").await;
+
+    assert!(sources.source("icap", &registry()).is_none());
+    let source = sources.source("icap", &hyperion_as_icap()).unwrap();
+    assert_eq!(source.backend(), Backend::Invictus);
+    assert_eq!(source.nav().await.unwrap().with_precision(3), "1.100");
+}
+
+#[tokio::test]
+async fn entries_match_every_name_of_the_fund() {
+    let (server, sources) = stand_in(&entries(&format!("[c20]\nsource = \"uniswap\"\ntoken = \"{}\"", ICAP))).await;
+    serve_subgraph(&server, V2_SUBGRAPH_PATH, "token_pairs.json").await;
+    let mut aliases = FundAliases::default();
+    aliases.aliases.insert("c20".into(), vec!["top20".into()]);
+    let registry = FundRegistry::builtin().with_aliases(&aliases);
+
+    for name in &["C20", "crypto20", "top20"] {
+        let source = sources.source(name, &registry).unwrap();
+        assert_eq!(source.backend(), Backend::Uniswap, "{}", name);
+    }
+    assert_eq!(sources.source("c10", &registry).unwrap().backend(), Backend::Invictus);
+}
+
+fn icap_chain() -> HashMap<String, SourceChain> {
+    entries(&format!("[[icap]]\nsource = \"uniswap\"\ntoken = \"{}\"\n[[icap]]\nsource = \"invictus\"", ICAP))
+}
+
+#[tokio::test]
+async fn failover_to_the_next_source() {
+    // no subgraph stand-in, the uniswap source fails
+    let (server, sources) = stand_in(&icap_chain()).await;
+    serve(&server, "funds", "funds.json").await;
+
+    let source = sources.source("icap", &hyperion_as_icap()).unwrap();
+    assert_eq!(source.backend(), Backend::Uniswap);
+    assert_eq!(source.nav().await.unwrap().with_precision(3), "1.100");
+}
+
+#[tokio::test]
+async fn failover_returns_the_last_error() {
+    let (_server, sources) = stand_in(&icap_chain()).await;
+
+    let error = sources.source("icap", &hyperion_as_icap()).unwrap().nav().await.unwrap_err();
+    assert!(error.downcast_ref::<InvictusError>().is_some());
+}
+
+#[tokio::test]
+async fn nav_readings_diverge() {
+    let (server, sources) = stand_in(&icap_chain()).await;
+    serve_subgraph(&server, V2_SUBGRAPH_PATH, "token_pairs.json").await;
+    serve(&server, "funds", "funds.json").await;
+
+    let readings = sources.chain("icap", &hyperion_as_icap()).unwrap().nav_readings().await;
+    assert_eq!(readings.nav().unwrap().with_precision(3), "4.066");
+    // (4.0666 - 1.1000005) / 1.1000005
+    assert_eq!(readings.spread().unwrap().with_precision(1), "269.6");
+    assert!(readings.diverge(DIVERGENCE_THRESHOLD));
+    assert_eq!(readings.to_string(), "uniswap $4.066, invictus $1.100");
+}
+
+#[tokio::test]
+async fn failed_readings_are_not_compared() {
+    let (server, sources) = stand_in(&icap_chain()).await;
+    serve(&server, "funds", "funds.json").await;
+
+    let readings = sources.chain("icap", &hyperion_as_icap()).unwrap().nav_readings().await;
+    assert_eq!(readings.nav().unwrap().with_precision(3), "1.100");
+    assert_eq!(readings.spread(), None);
+    assert!(!readings.diverge(DIVERGENCE_THRESHOLD));
+    assert_eq!(readings.failures().map(|(backend, _)| backend).collect::<Vec<_>>(), vec![Backend::Uniswap]);
+    assert_eq!(readings.to_string(), "uniswap failed, invictus $1.100");
+}
@@ -0,0 +1,48 @@
+//! Stand-in servers, recorded responses and request policies shared by the api libraries' tests.
+
+use std::sync::Arc;
+
+use api_policy::{PolicyConfig, RequestPolicy, RetryConfig};
+use wiremock::{MockServer, ResponseTemplate};
+
+/// Folder of recorded api responses.
+#[derive(Debug, Clone, Copy)]
+pub struct Fixtures {
+    dir: &'static str,
+}
+
+impl Fixtures {
+    /// Responses in `dir`, eg.: `concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures")`.
+    pub const fn new(dir: &'static str) -> Self {
+        Self { dir }
+    }
+
+    pub fn read(&self, name: &str) -> String {
+        let fixture_path = format!("{}/{}", self.dir, name);
+        std::fs::read_to_string(&fixture_path).unwrap_or_else(|e| panic!("can't read fixture {}: {}", fixture_path, e))
+    }
+
+    /// Json response with the `name` fixture as its body.
+    pub fn json_response(&self, status: u16, name: &str) -> ResponseTemplate {
+        ResponseTemplate::new(status).set_body_raw(self.read(name), "application/json")
+    }
+}
+
+/// Policy without retries, so error responses are returned right away.
+pub fn no_retry_policy() -> Arc<RequestPolicy> {
+    Arc::new(RequestPolicy::new(PolicyConfig {
+        retry: RetryConfig { max_retries: 0, ..RetryConfig::default() },
+        ..PolicyConfig::default()
+    }))
+}
+
+/// Local stand-in server and the client `client` builds pointed at it.
+pub async fn stand_in<C, F>(client: F) -> (MockServer, C)
+where
+    F: FnOnce(&MockServer) -> C,
+{
+    let server = MockServer::start().await;
+    let client = client(&server);
+    (server, client)
+}
+
@@ -0,0 +1,395 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use api_policy::RequestPolicy;
+use api_time::{system_clock, Clock, TimeRange};
+use etherscan_io_api::{BlockEstimator, EtherscanClient};
+
+use crate::graphql::Subgraph;
+use crate::queries::{BundleQuery, IndexedBlockQuery, PriceSamplesQuery, Swap, SwapsQuery, TokenPairsData, TokenPairsQuery, TokenQuery};
+use crate::{sample_blocks, time_weighted_average, PriceSample, DEFAULT_TWAP_SAMPLES, DEFAULT_TWAP_WINDOW};
+use crate::{BlockHeight, LiquidityStats, Query, Quote, SubgraphError, SwapCursor, SWAPS_PAGE, DAI, UNISWAP_V2, USDC, USDT, WETH};
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Pairs with less USD liquidity are left out of the weighted price.
+pub const DEFAULT_LIQUIDITY_FLOOR: f64 = 10_000.0;
+/// Tokens the weighted price looks up pairs against.
+pub const DEFAULT_QUOTE_TOKENS: [&str; 4] = [DAI, USDC, USDT, WETH];
+
+/// Uniswap V2 subgraph client pricing any token against a [`Quote`].
+/// Cloning is cheap, clones share the same connection pool.
+#[derive(Debug, Clone)]
+pub struct UniswapV2Client {
+    subgraph: Subgraph,
+    blocks: BlockEstimator,
+    clock: Arc<dyn Clock>,
+    liquidity_floor: f64,
+    quote_tokens: Vec<String>,
+    twap_samples: u32,
+    twap_window: TimeRange,
+}
+
+impl UniswapV2Client {
+    /// Client of the hosted subgraph with the default timeout.
+    pub fn new() -> Result<Self> {
+        Self::builder().build()
+    }
+
+    pub fn builder() -> UniswapV2ClientBuilder {
+        UniswapV2ClientBuilder::default()
+    }
+
+    pub fn subgraph_url(&self) -> &str {
+        &self.subgraph.url
+    }
+
+    /// Estimator the blocks of past timestamps come from.
+    pub fn block_estimator(&self) -> &BlockEstimator {
+        &self.blocks
+    }
+
+    /// Clock the performance ranges end at.
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// Latest price of `token` in `quote`.
+    pub async fn price(&self, token: &str, quote: &Quote) -> Result<f64> {
+        self.price_at(token, quote, None).await
+    }
+
+    /// Price of `token` in `quote` at the given block.
+    pub async fn price_at_block(&self, token: &str, quote: &Quote, block: i64) -> Result<f64> {
+        self.price_at(token, quote, Some(block)).await
+    }
+
+    /// Latest USD price of `token` averaged over its pairs against the quote tokens,
+    /// weighted by the pair liquidity. Pairs under the liquidity floor are left out.
+    pub async fn weighted_price(&self, token: &str) -> Result<f64> {
+        self.weighted_price_at(token, None).await
+    }
+
+    /// Liquidity weighted USD price of `token` at the given block.
+    pub async fn weighted_price_at_block(&self, token: &str, block: i64) -> Result<f64> {
+        self.weighted_price_at(token, Some(block)).await
+    }
+
+    /// Price change of `token` in `quote` over the range in percent,
+    /// the block at the start of the range comes from the block estimator.
+    pub async fn perf(&self, token: &str, quote: &Quote, range: TimeRange) -> Result<f64> {
+        Ok(self.perf_ranges(token, quote, &[range]).await?[0])
+    }
+
+    /// Price changes of `token` in `quote` over each range in percent, in the order of the ranges.
+    /// The blocks at the range starts come from the block estimator, the prices from a single subgraph request.
+    pub async fn perf_ranges(&self, token: &str, quote: &Quote, ranges: &[TimeRange]) -> Result<Vec<f64>> {
+        let now = self.clock.now();
+        let mut blocks = vec![None];
+        for range in ranges {
+            blocks.push(Some(range_start_block(&self.blocks, now, *range).await?));
+        }
+        let prices = self.prices_at_blocks(token, quote, &blocks).await?;
+
+        let price_now = prices[0].ok_or_else(|| anyhow!("token {} has no price", token))?;
+        blocks[1..].iter().zip(&prices[1..])
+            .map(|(block, price_previous)| match price_previous {
+                Some(price_previous) => Ok((price_now / price_previous - 1.0) * 100.0),
+                None => Err(anyhow!("token {} had no price at block {}", token, block.unwrap_or_default())),
+            })
+            .collect()
+    }
+
+    /// Time weighted average price of `token` in `quote` over the range,
+    /// sampled at evenly spaced blocks up to the latest indexed block in one request.
+    pub async fn twap(&self, token: &str, quote: &Quote, range: TimeRange) -> Result<f64> {
+        let range_start = range_start_block(&self.blocks, self.clock.now(), range).await?;
+        let latest = self.indexed_block().await?;
+        let samples = self.price_samples(token, quote, &sample_blocks(range_start, latest, self.twap_samples)).await?;
+        time_weighted_average(&samples).ok_or_else(|| anyhow!("token {} had no price over the past {}", token, range.label()))
+    }
+
+    /// Price change of `token` in `quote` over the range in percent, comparing the time weighted average prices
+    /// over the window before now and before the range start, so a single block can't move it.
+    /// Both windows are sampled in one request.
+    pub async fn twap_perf(&self, token: &str, quote: &Quote, range: TimeRange) -> Result<f64> {
+        let now = self.clock.now();
+        let window = self.twap_window.seconds_at(now).ok_or_else(|| before_epoch(self.twap_window))?;
+        let range_start = range_start(now, range)?;
+        let previous_end = block_at(&self.blocks, range_start).await?;
+        let previous_start = block_at(&self.blocks, range_start.checked_sub(window).ok_or_else(|| before_epoch(range))?).await?;
+        let current_start = block_at(&self.blocks, now.checked_sub(window).ok_or_else(|| before_epoch(self.twap_window))?).await?;
+        let latest = self.indexed_block().await?;
+
+        let previous_blocks = sample_blocks(previous_start, previous_end, self.twap_samples);
+        let current_blocks = sample_blocks(current_start, latest, self.twap_samples);
+        let blocks: Vec<_> = previous_blocks.iter().chain(&current_blocks).map(|&block| Some(block)).collect();
+        let prices = self.prices_at_blocks(token, quote, &blocks).await?;
+        let (previous, current) = prices.split_at(previous_blocks.len());
+
+        let twap_previous = time_weighted_average(&samples(&previous_blocks, previous))
+            .filter(|twap| *twap != 0.0)
+            .ok_or_else(|| anyhow!("token {} had no price before block {}", token, previous_end))?;
+        let twap_now = time_weighted_average(&samples(&current_blocks, current))
+            .ok_or_else(|| anyhow!("token {} has no price after block {}", token, current_start))?;
+        Ok((twap_now / twap_previous - 1.0) * 100.0)
+    }
+
+    /// Prices of `token` in `quote` at each block in one request,
+    /// blocks where the token or the quote had no price are left out.
+    pub async fn price_samples(&self, token: &str, quote: &Quote, blocks: &[i64]) -> Result<Vec<PriceSample>> {
+        let block_heights: Vec<_> = blocks.iter().map(|&block| Some(block)).collect();
+        let prices = self.prices_at_blocks(token, quote, &block_heights).await?;
+        Ok(samples(blocks, &prices))
+    }
+
+    /// Prices of `token` in `quote` at each block, `None` for the latest indexed block, in one aliased request.
+    /// The prices are in the order of the blocks, `None` where the token or the quote had no price.
+    pub async fn prices_at_blocks(&self, token: &str, quote: &Quote, blocks: &[Option<i64>]) -> Result<Vec<Option<f64>>> {
+        let quote_token = match quote {
+            Quote::Usd => None,
+            Quote::Token(quote_token) => Some(quote_token.to_lowercase()),
+        };
+        let quoted_in_token = quote_token.is_some();
+        let data = self.query(&PriceSamplesQuery { token: token.to_lowercase(), quote: quote_token, blocks: blocks.to_vec() }).await?;
+
+        let mut prices = Vec::with_capacity(blocks.len());
+        for index in 0..blocks.len() {
+            let token_eth = data.token_eth(index)?.unwrap_or(0.0);
+            let price = if quoted_in_token {
+                match data.quote_eth(index)? {
+                    Some(quote_eth) if quote_eth != 0.0 => token_eth / quote_eth,
+                    _ => 0.0,
+                }
+            } else {
+                token_eth * data.eth_price(index)?.unwrap_or(0.0)
+            };
+            prices.push(Some(price).filter(|&price| price != 0.0));
+        }
+        Ok(prices)
+    }
+
+    /// Latest block the subgraph indexed.
+    pub async fn indexed_block(&self) -> Result<i64, SubgraphError> {
+        Ok(self.query(&IndexedBlockQuery {}).await?.meta.block.number)
+    }
+
+    /// Liquidity, volume and trade count of the `token` pairs against the quote tokens over the range,
+    /// the block at the start of the range comes from the block estimator.
+    pub async fn liquidity(&self, token: &str, range: TimeRange) -> Result<LiquidityStats> {
+        let previous_block = range_start_block(&self.blocks, self.clock.now(), range).await?;
+        let now = self.token_pairs(token, None).await?;
+        let previous = self.token_pairs(token, Some(previous_block)).await?;
+
+        Ok(LiquidityStats::from_pairs(range, now.pairs(), previous.pairs())?)
+    }
+
+    /// Swaps on the pairs after the cursor, oldest first, at most [`SWAPS_PAGE`] per call.
+    pub async fn swaps_after(&self, pairs: &[String], cursor: &SwapCursor) -> Result<Vec<Swap>, SubgraphError> {
+        let data = self.query(&SwapsQuery {
+            pairs: pairs.iter().map(|pair| pair.to_lowercase()).collect(),
+            since: cursor.timestamp,
+            processed: cursor.processed_ids(),
+            first: SWAPS_PAGE,
+        }).await?;
+        Ok(cursor.newer(data.swaps))
+    }
+
+    /// Pairs of `token` against the quote tokens at the given block, the latest if `None`.
+    pub async fn token_pairs(&self, token: &str, block: Option<i64>) -> Result<TokenPairsData, SubgraphError> {
+        self.query(&TokenPairsQuery {
+            token: token.to_lowercase(),
+            quotes: self.quote_tokens.clone(),
+            block: BlockHeight::at(block),
+        }).await
+    }
+
+    /// Token price in ETH as derived by the subgraph from its pairs.
+    pub async fn derived_eth(&self, token: &str, block: Option<i64>) -> Result<f64> {
+        let data = self.query(&TokenQuery { id: token.to_lowercase(), block: BlockHeight::at(block) }).await?;
+        let token = data.token.ok_or(SubgraphError::MissingField { query: TokenQuery::OPERATION, field: "token" })?;
+        Ok(token.derived_eth()?)
+    }
+
+    /// ETH price in USD.
+    pub async fn eth_price(&self, block: Option<i64>) -> Result<f64> {
+        let data = self.query(&BundleQuery { block: BlockHeight::at(block) }).await?;
+        let bundle = data.bundle.ok_or(SubgraphError::MissingField { query: BundleQuery::OPERATION, field: "bundle" })?;
+        Ok(bundle.eth_price()?)
+    }
+
+    /// Runs a typed query under the retry and rate limit policy,
+    /// a response with an `errors` array is an error even if it carries partial data.
+    pub async fn query<Q: Query>(&self, variables: &Q) -> Result<Q::Data, SubgraphError> {
+        self.subgraph.query(variables).await
+    }
+
+    async fn price_at(&self, token: &str, quote: &Quote, block: Option<i64>) -> Result<f64> {
+        let token_eth = self.derived_eth(token, block).await?;
+        let quote_eth = match quote {
+            Quote::Usd => return Ok(token_eth * self.eth_price(block).await?),
+            Quote::Token(quote_token) => self.derived_eth(quote_token, block).await?,
+        };
+        if quote_eth == 0.0 {
+            return Err(anyhow!("quote token {} has no price", quote))
+        }
+        Ok(token_eth / quote_eth)
+    }
+
+    async fn weighted_price_at(&self, token: &str, block: Option<i64>) -> Result<f64> {
+        let data = self.token_pairs(token, block).await?;
+        weighted_usd_price(token, &data, self.liquidity_floor)
+    }
+}
+
+fn samples(blocks: &[i64], prices: &[Option<f64>]) -> Vec<PriceSample> {
+    blocks.iter().zip(prices)
+        .filter_map(|(&block, price)| price.map(|price| PriceSample { block, price }))
+        .collect()
+}
+
+pub(crate) async fn range_start_block(blocks: &BlockEstimator, now: u64, range: TimeRange) -> Result<i64> {
+    block_at(blocks, range_start(now, range)?).await
+}
+
+fn range_start(now: u64, range: TimeRange) -> Result<u64> {
+    range.start_at(now).ok_or_else(|| before_epoch(range))
+}
+
+fn before_epoch(range: TimeRange) -> anyhow::Error {
+    anyhow!("the past {} starts before the unix epoch", range.label())
+}
+
+async fn block_at(blocks: &BlockEstimator, timestamp: u64) -> Result<i64> {
+    Ok(blocks.block_at(timestamp).await?)
+}
+
+// Each pair prices the token in its other token, valued in USD through the subgraph ETH price.
+// Pairs under the floor or against a token without a price don't count.
+fn weighted_usd_price(token: &str, data: &TokenPairsData, liquidity_floor: f64) -> Result<f64> {
+    let bundle = data.bundle.as_ref().ok_or(SubgraphError::MissingField { query: TokenPairsQuery::OPERATION, field: "bundle" })?;
+    let eth_price = bundle.eth_price()?;
+
+    let as_token0 = data.as_token0.iter().map(|pair| (pair, &pair.token1, pair.token1_price(TokenPairsQuery::OPERATION)));
+    let as_token1 = data.as_token1.iter().map(|pair| (pair, &pair.token0, pair.token0_price(TokenPairsQuery::OPERATION)));
+    let mut weighted_sum = 0.0;
+    let mut liquidity = 0.0;
+    for (pair, other, price_in_other) in as_token0.chain(as_token1) {
+        let reserve_usd = pair.reserve_usd(TokenPairsQuery::OPERATION)?;
+        if reserve_usd < liquidity_floor || pair.reserve0(TokenPairsQuery::OPERATION)? == 0.0 || pair.reserve1(TokenPairsQuery::OPERATION)? == 0.0 {
+            continue
+        }
+        let other_usd = other.derived_eth()? * eth_price;
+        if other_usd == 0.0 {
+            continue
+        }
+        weighted_sum += price_in_other? * other_usd * reserve_usd;
+        liquidity += reserve_usd;
+    }
+    if liquidity == 0.0 {
+        return Err(anyhow!("token {} has no priced pair with at least ${} liquidity", token, liquidity_floor))
+    }
+    Ok(weighted_sum / liquidity)
+}
+
+/// Builder for [`UniswapV2Client`], every option falls back to a sane default.
+#[derive(Debug, Clone)]
+pub struct UniswapV2ClientBuilder {
+    subgraph_url: String,
+    timeout: Duration,
+    policy: Option<Arc<RequestPolicy>>,
+    blocks: Option<BlockEstimator>,
+    clock: Option<Arc<dyn Clock>>,
+    liquidity_floor: f64,
+    quote_tokens: Vec<String>,
+    twap_samples: u32,
+    twap_window: TimeRange,
+}
+
+impl Default for UniswapV2ClientBuilder {
+    fn default() -> Self {
+        Self {
+            subgraph_url: UNISWAP_V2.into(),
+            timeout: DEFAULT_TIMEOUT,
+            policy: None,
+            blocks: None,
+            clock: None,
+            liquidity_floor: DEFAULT_LIQUIDITY_FLOOR,
+            quote_tokens: DEFAULT_QUOTE_TOKENS.iter().map(|token| token.to_string()).collect(),
+            twap_samples: DEFAULT_TWAP_SAMPLES,
+            twap_window: DEFAULT_TWAP_WINDOW,
+        }
+    }
+}
+
+impl UniswapV2ClientBuilder {
+    /// Subgraph url, eg.: a local mirror or mock server.
+    pub fn subgraph_url(mut self, subgraph_url: &str) -> Self {
+        self.subgraph_url = subgraph_url.into();
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Retry and rate limit policy, the process wide [`RequestPolicy::global`] if not set.
+    pub fn request_policy(mut self, policy: Arc<RequestPolicy>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Estimator the blocks of past timestamps come from, share one between clients to share its anchors.
+    /// An estimator of [`EtherscanClient::from_env`] without a cache file if not set.
+    pub fn block_estimator(mut self, blocks: BlockEstimator) -> Self {
+        self.blocks = Some(blocks);
+        self
+    }
+
+    /// Clock the performance ranges end at, the system clock if not set.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Minimum USD liquidity of a pair counted in the weighted price.
+    pub fn liquidity_floor(mut self, liquidity_floor: f64) -> Self {
+        self.liquidity_floor = liquidity_floor;
+        self
+    }
+
+    /// Token addresses the weighted price looks up pairs against.
+    pub fn quote_tokens(mut self, quote_tokens: &[&str]) -> Self {
+        self.quote_tokens = quote_tokens.iter().map(|token| token.to_lowercase()).collect();
+        self
+    }
+
+    /// Blocks sampled per time weighted average price, at least 2 to span the period.
+    pub fn twap_samples(mut self, twap_samples: u32) -> Self {
+        self.twap_samples = twap_samples;
+        self
+    }
+
+    /// Period before now and before the range start that [`UniswapV2Client::twap_perf`] averages the price over.
+    pub fn twap_window(mut self, twap_window: TimeRange) -> Self {
+        self.twap_window = twap_window;
+        self
+    }
+
+    pub fn build(self) -> Result<UniswapV2Client> {
+        let blocks = match self.blocks {
+            Some(blocks) => blocks,
+            None => BlockEstimator::new(EtherscanClient::from_env()?),
+        };
+        Ok(UniswapV2Client {
+            subgraph: Subgraph::new(self.subgraph_url, self.timeout, self.policy)?,
+            blocks,
+            clock: self.clock.unwrap_or_else(system_clock),
+            liquidity_floor: self.liquidity_floor,
+            quote_tokens: self.quote_tokens,
+            twap_samples: self.twap_samples,
+            twap_window: self.twap_window,
+        })
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt;
+
+use anyhow::Result;
+use api_time::TimeRange;
+use async_trait::async_trait;
+use serde_derive::Deserialize;
+
+use crate::queries::TokenPairsQuery;
+use crate::{Query, Quote, UniswapV2Client, UniswapV3Client};
+
+/// Uniswap version a bot reads token prices from, `v2` or `v3` in the bot config.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DexVersion {
+    #[default]
+    V2,
+    V3,
+}
+
+impl fmt::Display for DexVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DexVersion::V2 => f.write_str("uniswap v2"),
+            DexVersion::V3 => f.write_str("uniswap v3"),
+        }
+    }
+}
+
+/// Token prices and liquidity of a DEX subgraph, so the bots can switch Uniswap versions in the config.
+#[async_trait]
+pub trait Dex: fmt::Debug + Send + Sync {
+    fn version(&self) -> DexVersion;
+
+    /// Latest price of `token` in `quote`.
+    async fn price(&self, token: &str, quote: &Quote) -> Result<f64>;
+
+    /// Price of `token` in `quote` at the given block.
+    async fn price_at_block(&self, token: &str, quote: &Quote, block: i64) -> Result<f64>;
+
+    /// Latest USD price of `token` weighted by the liquidity of its pools against the quote tokens.
+    async fn usd_price(&self, token: &str) -> Result<f64>;
+
+    /// Price change of `token` in `quote` over the range in percent.
+    async fn perf(&self, token: &str, quote: &Quote, range: TimeRange) -> Result<f64>;
+
+    /// Price change of `token` in `quote` over each range in percent, in the order of the ranges.
+    async fn perf_ranges(&self, token: &str, quote: &Quote, ranges: &[TimeRange]) -> Result<Vec<f64>> {
+        let mut perf = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            perf.push(self.perf(token, quote, *range).await?);
+        }
+        Ok(perf)
+    }
+
+    /// Time weighted average price change of `token` in `quote` over the range in percent,
+    /// the spot price change where the subgraph client has no time weighted prices.
+    async fn twap_perf(&self, token: &str, quote: &Quote, range: TimeRange) -> Result<f64> {
+        self.perf(token, quote, range).await
+    }
+
+    /// USD value in the `token` pools against the quote tokens.
+    async fn liquidity_usd(&self, token: &str) -> Result<f64>;
+}
+
+#[async_trait]
+impl Dex for UniswapV2Client {
+    fn version(&self) -> DexVersion {
+        DexVersion::V2
+    }
+
+    async fn price(&self, token: &str, quote: &Quote) -> Result<f64> {
+        UniswapV2Client::price(self, token, quote).await
+    }
+
+    async fn price_at_block(&self, token: &str, quote: &Quote, block: i64) -> Result<f64> {
+        UniswapV2Client::price_at_block(self, token, quote, block).await
+    }
+
+    async fn usd_price(&self, token: &str) -> Result<f64> {
+        self.weighted_price(token).await
+    }
+
+    async fn perf(&self, token: &str, quote: &Quote, range: TimeRange) -> Result<f64> {
+        UniswapV2Client::perf(self, token, quote, range).await
+    }
+
+    async fn perf_ranges(&self, token: &str, quote: &Quote, ranges: &[TimeRange]) -> Result<Vec<f64>> {
+        UniswapV2Client::perf_ranges(self, token, quote, ranges).await
+    }
+
+    async fn twap_perf(&self, token: &str, quote: &Quote, range: TimeRange) -> Result<f64> {
+        UniswapV2Client::twap_perf(self, token, quote, range).await
+    }
+
+    async fn liquidity_usd(&self, token: &str) -> Result<f64> {
+        let data = self.token_pairs(token, None).await?;
+        let mut liquidity = 0.0;
+        for pair in data.pairs() {
+            liquidity += pair.reserve_usd(TokenPairsQuery::OPERATION)?;
+        }
+        Ok(liquidity)
+    }
+}
+
+#[async_trait]
+impl Dex for UniswapV3Client {
+    fn version(&self) -> DexVersion {
+        DexVersion::V3
+    }
+
+    async fn price(&self, token: &str, quote: &Quote) -> Result<f64> {
+        UniswapV3Client::price(self, token, quote).await
+    }
+
+    async fn price_at_block(&self, token: &str, quote: &Quote, block: i64) -> Result<f64> {
+        UniswapV3Client::price_at_block(self, token, quote, block).await
+    }
+
+    async fn usd_price(&self, token: &str) -> Result<f64> {
+        UniswapV3Client::price(self, token, &Quote::Usd).await
+    }
+
+    async fn perf(&self, token: &str, quote: &Quote, range: TimeRange) -> Result<f64> {
+        UniswapV3Client::perf(self, token, quote, range).await
+    }
+
+    async fn liquidity_usd(&self, token: &str) -> Result<f64> {
+        let fee_tiers = self.liquidity_by_fee_tier(token).await?;
+        Ok(fee_tiers.iter().map(|tier| tier.total_value_locked_usd).sum())
+    }
+}
@@ -0,0 +1,123 @@
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use api_policy::RequestPolicy;
+use serde::de::DeserializeOwned;
+use serde_derive::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Typed subgraph query, the implementing struct holds the query variables.
+pub trait Query: serde::Serialize {
+    /// Operation name, used in error messages.
+    const OPERATION: &'static str;
+    /// Shape of the `data` field of the response.
+    type Data: DeserializeOwned;
+
+    /// GraphQL document declaring the operation, fixed for most queries.
+    fn document(&self) -> Cow<'static, str>;
+}
+
+#[derive(Debug, Error)]
+pub enum SubgraphError {
+    #[error("http error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("subgraph responded with http status {0}")]
+    HttpStatus(u16),
+    #[error("{query} query failed: {}", messages.join("; "))]
+    Graphql { query: &'static str, messages: Vec<String> },
+    #[error("{query} query response can't be decoded: {source}")]
+    Decode { query: &'static str, source: serde_json::Error },
+    #[error("{query} query returned no {field}")]
+    MissingField { query: &'static str, field: &'static str },
+    #[error("{query} query field {field} is not a number: {value}")]
+    InvalidNumber { query: &'static str, field: &'static str, value: String },
+}
+
+/// Block a query reads the entities at, `None` in the variables queries the latest indexed block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHeight {
+    pub number: i64,
+}
+
+impl BlockHeight {
+    pub fn at(block: Option<i64>) -> Option<Self> {
+        block.map(|number| Self { number })
+    }
+}
+
+/// Subgraph endpoint with its connection pool and request policy, shared by the V2 and V3 clients.
+#[derive(Debug, Clone)]
+pub(crate) struct Subgraph {
+    pub http: reqwest::Client,
+    pub url: String,
+    pub policy: Arc<RequestPolicy>,
+}
+
+impl Subgraph {
+    /// The process wide [`RequestPolicy::global`] applies if no policy is given.
+    pub fn new(url: String, timeout: Duration, policy: Option<Arc<RequestPolicy>>) -> Result<Self, reqwest::Error> {
+        let http = reqwest::Client::builder()
+            .timeout(timeout)
+            .connect_timeout(timeout)
+            .build()?;
+        Ok(Self { http, url, policy: policy.unwrap_or_else(RequestPolicy::global) })
+    }
+
+    /// Runs a typed query under the retry and rate limit policy,
+    /// a response with an `errors` array is an error even if it carries partial data.
+    pub async fn query<Q: Query>(&self, variables: &Q) -> Result<Q::Data, SubgraphError> {
+        let request = self.http
+            .post(&self.url)
+            .json(&Request { operation_name: Q::OPERATION, variables, query: &variables.document() })
+            .build()?;
+        let response = self.policy.execute(&self.http, request).await?;
+        let http_status = response.status();
+        let body = response.bytes().await?;
+
+        let response: Response<Q::Data> = match serde_json::from_slice(&body) {
+            Ok(response) => response,
+            Err(_) if !http_status.is_success() => return Err(SubgraphError::HttpStatus(http_status.as_u16())),
+            Err(source) => return Err(SubgraphError::Decode { query: Q::OPERATION, source }),
+        };
+        if !response.errors.is_empty() {
+            let messages = response.errors.into_iter().map(|error| error.message).collect();
+            return Err(SubgraphError::Graphql { query: Q::OPERATION, messages })
+        }
+        match response.data {
+            Some(data) => Ok(data),
+            None if !http_status.is_success() => Err(SubgraphError::HttpStatus(http_status.as_u16())),
+            None => Err(SubgraphError::MissingField { query: Q::OPERATION, field: "data" }),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Request<'a, Q> {
+    pub operation_name: &'static str,
+    pub variables: &'a Q,
+    pub query: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Response<T> {
+    pub data: Option<T>,
+    #[serde(default)]
+    pub errors: Vec<ResponseError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ResponseError {
+    pub message: String,
+}
+
+// The subgraph encodes BigDecimal and BigInt fields as strings.
+pub(crate) fn parse_number<T: FromStr>(query: &'static str, field: &'static str, value: &str) -> Result<T, SubgraphError>
+where
+    T::Err: Display,
+{
+    value.parse().map_err(|_| SubgraphError::InvalidNumber { query, field, value: value.to_string() })
+}
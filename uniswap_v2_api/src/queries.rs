@@ -0,0 +1,425 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::graphql::{parse_number, BlockHeight, Query, SubgraphError};
+
+/// Token price in ETH, `token` entity.
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenQuery {
+    pub id: String,
+    pub block: Option<BlockHeight>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenData {
+    pub token: Option<Token>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Token {
+    #[serde(rename = "derivedETH")]
+    pub derived_eth: String,
+}
+
+impl Query for TokenQuery {
+    const OPERATION: &'static str = "TokenPrice";
+    type Data = TokenData;
+
+    fn document(&self) -> Cow<'static, str> {
+        Cow::Borrowed("query TokenPrice($id: String!, $block: Block_height) {\n  token(id: $id, block: $block) {\n    derivedETH\n  }\n}")
+    }
+}
+
+impl Token {
+    pub fn derived_eth(&self) -> Result<f64, SubgraphError> {
+        parse_number(TokenQuery::OPERATION, "token.derivedETH", &self.derived_eth)
+    }
+}
+
+/// ETH price in USD, `bundle` entity.
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleQuery {
+    pub block: Option<BlockHeight>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BundleData {
+    pub bundle: Option<Bundle>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Bundle {
+    #[serde(rename = "ethPrice")]
+    pub eth_price: String,
+}
+
+impl Query for BundleQuery {
+    const OPERATION: &'static str = "EthPrice";
+    type Data = BundleData;
+
+    fn document(&self) -> Cow<'static, str> {
+        Cow::Borrowed("query EthPrice($block: Block_height) {\n  bundle(id: 1, block: $block) {\n    ethPrice\n  }\n}")
+    }
+}
+
+impl Bundle {
+    pub fn eth_price(&self) -> Result<f64, SubgraphError> {
+        parse_number(BundleQuery::OPERATION, "bundle.ethPrice", &self.eth_price)
+    }
+}
+
+/// Pairs of the two tokens in the given order, `pairs` entities.
+#[derive(Debug, Clone, Serialize)]
+pub struct PairsQuery {
+    pub token0: String,
+    pub token1: String,
+    pub block: Option<BlockHeight>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PairsData {
+    pub pairs: Vec<Pair>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PairToken {
+    pub id: String,
+    pub symbol: String,
+    /// Only requested by [`TokenPairsQuery`].
+    #[serde(rename = "derivedETH", default)]
+    pub derived_eth: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Pair {
+    pub id: String,
+    pub token0: PairToken,
+    pub token1: PairToken,
+    pub reserve0: String,
+    pub reserve1: String,
+    #[serde(rename = "token0Price")]
+    pub token0_price: String,
+    #[serde(rename = "token1Price")]
+    pub token1_price: String,
+    #[serde(rename = "reserveUSD")]
+    pub reserve_usd: String,
+    #[serde(rename = "volumeUSD")]
+    pub volume_usd: String,
+    #[serde(rename = "txCount")]
+    pub tx_count: String,
+}
+
+impl Query for PairsQuery {
+    const OPERATION: &'static str = "Pairs";
+    type Data = PairsData;
+
+    fn document(&self) -> Cow<'static, str> {
+        Cow::Borrowed("query Pairs($token0: String!, $token1: String!, $block: Block_height) {\n  pairs(where: {token0: $token0, token1: $token1}, block: $block) {\n    id\n    token0 {\n      id\n      symbol\n    }\n    token1 {\n      id\n      symbol\n    }\n    reserve0\n    reserve1\n    token0Price\n    token1Price\n    reserveUSD\n    volumeUSD\n    txCount\n  }\n}")
+    }
+}
+
+// Pairs are read by several queries, the accessors take the operation of the one that read the pair for the errors.
+impl Pair {
+    /// Token0 amount per token1, ie.: the price of token1 in token0.
+    pub fn token0_price(&self, query: &'static str) -> Result<f64, SubgraphError> {
+        parse_number(query, "pairs.token0Price", &self.token0_price)
+    }
+
+    /// Token1 amount per token0, ie.: the price of token0 in token1.
+    pub fn token1_price(&self, query: &'static str) -> Result<f64, SubgraphError> {
+        parse_number(query, "pairs.token1Price", &self.token1_price)
+    }
+
+    pub fn reserve0(&self, query: &'static str) -> Result<f64, SubgraphError> {
+        parse_number(query, "pairs.reserve0", &self.reserve0)
+    }
+
+    pub fn reserve1(&self, query: &'static str) -> Result<f64, SubgraphError> {
+        parse_number(query, "pairs.reserve1", &self.reserve1)
+    }
+
+    pub fn reserve_usd(&self, query: &'static str) -> Result<f64, SubgraphError> {
+        parse_number(query, "pairs.reserveUSD", &self.reserve_usd)
+    }
+
+    pub fn volume_usd(&self, query: &'static str) -> Result<f64, SubgraphError> {
+        parse_number(query, "pairs.volumeUSD", &self.volume_usd)
+    }
+
+    pub fn tx_count(&self, query: &'static str) -> Result<u64, SubgraphError> {
+        parse_number(query, "pairs.txCount", &self.tx_count)
+    }
+}
+
+/// Pairs of the token against any of the quote tokens, in both token orders, with the ETH price to value them.
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenPairsQuery {
+    pub token: String,
+    pub quotes: Vec<String>,
+    pub block: Option<BlockHeight>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenPairsData {
+    /// Pairs with the token as token0.
+    #[serde(rename = "asToken0")]
+    pub as_token0: Vec<Pair>,
+    /// Pairs with the token as token1.
+    #[serde(rename = "asToken1")]
+    pub as_token1: Vec<Pair>,
+    pub bundle: Option<Bundle>,
+}
+
+impl Query for TokenPairsQuery {
+    const OPERATION: &'static str = "TokenPairs";
+    type Data = TokenPairsData;
+
+    fn document(&self) -> Cow<'static, str> {
+        Cow::Borrowed("query TokenPairs($token: String!, $quotes: [String!]!, $block: Block_height) {\n  asToken0: pairs(where: {token0: $token, token1_in: $quotes}, block: $block) {\n    ...PairFields\n  }\n  asToken1: pairs(where: {token0_in: $quotes, token1: $token}, block: $block) {\n    ...PairFields\n  }\n  bundle(id: 1, block: $block) {\n    ethPrice\n  }\n}\nfragment PairFields on Pair {\n  id\n  token0 {\n    id\n    symbol\n    derivedETH\n  }\n  token1 {\n    id\n    symbol\n    derivedETH\n  }\n  reserve0\n  reserve1\n  token0Price\n  token1Price\n  reserveUSD\n  volumeUSD\n  txCount\n}")
+    }
+}
+
+impl TokenPairsData {
+    /// Pairs in either token order.
+    pub fn pairs(&self) -> impl Iterator<Item = &Pair> + Clone {
+        self.as_token0.iter().chain(&self.as_token1)
+    }
+}
+
+impl PairToken {
+    pub fn derived_eth(&self) -> Result<f64, SubgraphError> {
+        let derived_eth = self.derived_eth.as_deref()
+            .ok_or(SubgraphError::MissingField { query: TokenPairsQuery::OPERATION, field: "pairs.token.derivedETH" })?;
+        parse_number(TokenPairsQuery::OPERATION, "pairs.token.derivedETH", derived_eth)
+    }
+}
+
+/// Daily token statistics, newest first, `tokenDayDatas` entities.
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenDayDatasQuery {
+    pub token: String,
+    pub days: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenDayDatasData {
+    #[serde(rename = "tokenDayDatas")]
+    pub token_day_datas: Vec<TokenDayData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenDayData {
+    /// Unix timestamp of the start of the day.
+    pub date: i64,
+    #[serde(rename = "priceUSD")]
+    pub price_usd: String,
+    #[serde(rename = "totalLiquidityUSD")]
+    pub total_liquidity_usd: String,
+    #[serde(rename = "dailyVolumeUSD")]
+    pub daily_volume_usd: String,
+}
+
+impl Query for TokenDayDatasQuery {
+    const OPERATION: &'static str = "TokenDayDatas";
+    type Data = TokenDayDatasData;
+
+    fn document(&self) -> Cow<'static, str> {
+        Cow::Borrowed("query TokenDayDatas($token: String!, $days: Int!) {\n  tokenDayDatas(first: $days, orderBy: date, orderDirection: desc, where: {token: $token}) {\n    date\n    priceUSD\n    totalLiquidityUSD\n    dailyVolumeUSD\n  }\n}")
+    }
+}
+
+impl TokenDayData {
+    pub fn price_usd(&self) -> Result<f64, SubgraphError> {
+        parse_number(TokenDayDatasQuery::OPERATION, "tokenDayDatas.priceUSD", &self.price_usd)
+    }
+
+    pub fn total_liquidity_usd(&self) -> Result<f64, SubgraphError> {
+        parse_number(TokenDayDatasQuery::OPERATION, "tokenDayDatas.totalLiquidityUSD", &self.total_liquidity_usd)
+    }
+
+    pub fn daily_volume_usd(&self) -> Result<f64, SubgraphError> {
+        parse_number(TokenDayDatasQuery::OPERATION, "tokenDayDatas.dailyVolumeUSD", &self.daily_volume_usd)
+    }
+}
+
+/// Swaps on the given pairs from the `since` unix timestamp, oldest first, `swaps` entities.
+#[derive(Debug, Clone, Serialize)]
+pub struct SwapsQuery {
+    pub pairs: Vec<String>,
+    pub since: i64,
+    /// Ids left out, so a page isn't filled with swaps processed at `since` already.
+    pub processed: Vec<String>,
+    pub first: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SwapsData {
+    pub swaps: Vec<Swap>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Swap {
+    /// Transaction hash and log index, eg.: `0xabc..-3`.
+    pub id: String,
+    pub timestamp: String,
+    pub pair: SwapPair,
+    #[serde(rename = "amount0In")]
+    pub amount0_in: String,
+    #[serde(rename = "amount1In")]
+    pub amount1_in: String,
+    #[serde(rename = "amount0Out")]
+    pub amount0_out: String,
+    #[serde(rename = "amount1Out")]
+    pub amount1_out: String,
+    #[serde(rename = "amountUSD")]
+    pub amount_usd: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SwapPair {
+    pub id: String,
+    pub token0: PairToken,
+    pub token1: PairToken,
+}
+
+impl Query for SwapsQuery {
+    const OPERATION: &'static str = "Swaps";
+    type Data = SwapsData;
+
+    fn document(&self) -> Cow<'static, str> {
+        Cow::Borrowed("query Swaps($pairs: [String!]!, $since: BigInt!, $processed: [ID!]!, $first: Int!) {\n  swaps(first: $first, orderBy: timestamp, orderDirection: asc, where: {pair_in: $pairs, timestamp_gte: $since, id_not_in: $processed}) {\n    id\n    timestamp\n    pair {\n      id\n      token0 {\n        id\n        symbol\n      }\n      token1 {\n        id\n        symbol\n      }\n    }\n    amount0In\n    amount1In\n    amount0Out\n    amount1Out\n    amountUSD\n  }\n}")
+    }
+}
+
+impl Swap {
+    pub fn timestamp(&self) -> Result<i64, SubgraphError> {
+        parse_number(SwapsQuery::OPERATION, "swaps.timestamp", &self.timestamp)
+    }
+
+    pub fn amount_usd(&self) -> Result<f64, SubgraphError> {
+        parse_number(SwapsQuery::OPERATION, "swaps.amountUSD", &self.amount_usd)
+    }
+
+    /// Transaction hash of the swap.
+    pub fn transaction(&self) -> &str {
+        self.id.split('-').next().unwrap_or(&self.id)
+    }
+
+    /// Amount of `token` bought, negative if sold, zero if it's not traded in the pair.
+    pub fn token_amount(&self, token: &str) -> Result<f64, SubgraphError> {
+        let (amount_in, amount_out, field_in, field_out) = if self.pair.token0.id.eq_ignore_ascii_case(token) {
+            (&self.amount0_in, &self.amount0_out, "swaps.amount0In", "swaps.amount0Out")
+        } else if self.pair.token1.id.eq_ignore_ascii_case(token) {
+            (&self.amount1_in, &self.amount1_out, "swaps.amount1In", "swaps.amount1Out")
+        } else {
+            return Ok(0.0)
+        };
+        let amount_in: f64 = parse_number(SwapsQuery::OPERATION, field_in, amount_in)?;
+        let amount_out: f64 = parse_number(SwapsQuery::OPERATION, field_out, amount_out)?;
+        Ok(amount_out - amount_in)
+    }
+}
+
+/// Latest block indexed by the subgraph, `_meta` field.
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexedBlockQuery {}
+
+#[derive(Debug, Deserialize)]
+pub struct IndexedBlockData {
+    #[serde(rename = "_meta")]
+    pub meta: Meta,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Meta {
+    pub block: BlockHeight,
+}
+
+impl Query for IndexedBlockQuery {
+    const OPERATION: &'static str = "IndexedBlock";
+    type Data = IndexedBlockData;
+
+    fn document(&self) -> Cow<'static, str> {
+        Cow::Borrowed("query IndexedBlock {\n  _meta {\n    block {\n      number\n    }\n  }\n}")
+    }
+}
+
+/// Token price in ETH with the ETH price in USD, or the quote token price in ETH, at each block in one request.
+/// Entities are aliased by block index, eg.: `token0`, `eth0` or `quote0`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PriceSamplesQuery {
+    pub token: String,
+    /// Quote token, the ETH price is sampled if `None`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote: Option<String>,
+    /// Inlined in the document, `None` reads the latest indexed block.
+    #[serde(skip)]
+    pub blocks: Vec<Option<i64>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(transparent)]
+pub struct PriceSamplesData(pub HashMap<String, Option<SampledEntity>>);
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum SampledEntity {
+    Token(Token),
+    Bundle(Bundle),
+}
+
+impl Query for PriceSamplesQuery {
+    const OPERATION: &'static str = "PriceSamples";
+    type Data = PriceSamplesData;
+
+    fn document(&self) -> Cow<'static, str> {
+        let mut document = String::from("query PriceSamples($token: String!");
+        if self.quote.is_some() {
+            document.push_str(", $quote: String!");
+        }
+        document.push_str(") {\n");
+        for (index, block) in self.blocks.iter().enumerate() {
+            let block = match block {
+                Some(number) => format!(", block: {{number: {}}}", number),
+                None => String::new(),
+            };
+            document.push_str(&format!("  token{}: token(id: $token{}) {{\n    derivedETH\n  }}\n", index, block));
+            if self.quote.is_some() {
+                document.push_str(&format!("  quote{}: token(id: $quote{}) {{\n    derivedETH\n  }}\n", index, block));
+            } else {
+                document.push_str(&format!("  eth{}: bundle(id: 1{}) {{\n    ethPrice\n  }}\n", index, block));
+            }
+        }
+        document.push('}');
+        Cow::Owned(document)
+    }
+}
+
+impl PriceSamplesData {
+    /// Token price in ETH at the block of the index, `None` if the token didn't exist yet.
+    pub fn token_eth(&self, index: usize) -> Result<Option<f64>, SubgraphError> {
+        self.sampled(&format!("token{}", index), "token.derivedETH")
+    }
+
+    /// Quote token price in ETH at the block of the index.
+    pub fn quote_eth(&self, index: usize) -> Result<Option<f64>, SubgraphError> {
+        self.sampled(&format!("quote{}", index), "quote.derivedETH")
+    }
+
+    /// ETH price in USD at the block of the index.
+    pub fn eth_price(&self, index: usize) -> Result<Option<f64>, SubgraphError> {
+        self.sampled(&format!("eth{}", index), "bundle.ethPrice")
+    }
+
+    fn sampled(&self, alias: &str, field: &'static str) -> Result<Option<f64>, SubgraphError> {
+        let value = match self.0.get(alias) {
+            Some(Some(SampledEntity::Token(token))) => &token.derived_eth,
+            Some(Some(SampledEntity::Bundle(bundle))) => &bundle.eth_price,
+            Some(None) => return Ok(None),
+            None => return Err(SubgraphError::MissingField { query: PriceSamplesQuery::OPERATION, field }),
+        };
+        parse_number(PriceSamplesQuery::OPERATION, field, value).map(Some)
+    }
+}
@@ -0,0 +1,68 @@
+use api_time::TimeRange;
+
+use crate::queries::{Pair, TokenPairsQuery};
+use crate::{Query, SubgraphError};
+
+/// Pair liquidity now and its change over a time range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairStats {
+    pub id: String,
+    /// Pair symbols, eg.: `DAI/ICAP`.
+    pub name: String,
+    pub reserve_usd: f64,
+    /// Change of the pooled value over the range.
+    pub reserve_usd_delta: f64,
+    /// Volume traded over the range.
+    pub volume_usd_delta: f64,
+    /// Swaps, mints and burns over the range.
+    pub tx_count_delta: u64,
+}
+
+/// Liquidity of a token's pairs over a time range, largest pair first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiquidityStats {
+    pub range: TimeRange,
+    pub pairs: Vec<PairStats>,
+}
+
+impl LiquidityStats {
+    /// Matches the current pairs with their state at the start of the range,
+    /// pairs created within the range count from zero.
+    pub(crate) fn from_pairs<'a>(range: TimeRange, now: impl Iterator<Item = &'a Pair>, previous: impl Iterator<Item = &'a Pair> + Clone) -> Result<Self, SubgraphError> {
+        const QUERY: &str = TokenPairsQuery::OPERATION;
+        let mut pairs = Vec::new();
+        for pair in now {
+            let (reserve_usd, volume_usd, tx_count) = (pair.reserve_usd(QUERY)?, pair.volume_usd(QUERY)?, pair.tx_count(QUERY)?);
+            let (previous_reserve_usd, previous_volume_usd, previous_tx_count) = match previous.clone().find(|previous| previous.id == pair.id) {
+                Some(previous) => (previous.reserve_usd(QUERY)?, previous.volume_usd(QUERY)?, previous.tx_count(QUERY)?),
+                None => (0.0, 0.0, 0),
+            };
+            pairs.push(PairStats {
+                id: pair.id.clone(),
+                name: format!("{}/{}", pair.token0.symbol, pair.token1.symbol),
+                reserve_usd,
+                reserve_usd_delta: reserve_usd - previous_reserve_usd,
+                volume_usd_delta: volume_usd - previous_volume_usd,
+                tx_count_delta: tx_count.saturating_sub(previous_tx_count),
+            });
+        }
+        pairs.sort_by(|a, b| b.reserve_usd.partial_cmp(&a.reserve_usd).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(Self { range, pairs })
+    }
+
+    pub fn reserve_usd(&self) -> f64 {
+        self.pairs.iter().map(|pair| pair.reserve_usd).sum()
+    }
+
+    pub fn reserve_usd_delta(&self) -> f64 {
+        self.pairs.iter().map(|pair| pair.reserve_usd_delta).sum()
+    }
+
+    pub fn volume_usd_delta(&self) -> f64 {
+        self.pairs.iter().map(|pair| pair.volume_usd_delta).sum()
+    }
+
+    pub fn tx_count_delta(&self) -> u64 {
+        self.pairs.iter().map(|pair| pair.tx_count_delta).sum()
+    }
+}
@@ -0,0 +1,60 @@
+use serde_derive::{Deserialize, Serialize};
+
+use crate::queries::Swap;
+use crate::SubgraphError;
+
+/// Swaps fetched per [`UniswapV2Client::swaps_after`](crate::UniswapV2Client::swaps_after) call.
+pub const SWAPS_PAGE: u32 = 100;
+
+/// Position of the last processed swap, pollers persist it so restarts neither re-announce nor skip swaps.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapCursor {
+    /// Unix timestamp of the block of the swap.
+    pub timestamp: i64,
+    /// Swap id, empty before the first processed swap.
+    pub id: String,
+    /// Ids of the swaps processed at `timestamp`, the subgraph only orders swaps by their timestamp.
+    #[serde(default)]
+    pub processed: Vec<String>,
+}
+
+impl SwapCursor {
+    /// Cursor before any swap from `timestamp` on.
+    pub fn starting_at(timestamp: i64) -> Self {
+        Self { timestamp, id: String::new(), processed: Vec::new() }
+    }
+
+    pub fn of(swap: &Swap) -> Result<Self, SubgraphError> {
+        Ok(Self { timestamp: swap.timestamp()?, id: swap.id.clone(), processed: vec![swap.id.clone()] })
+    }
+
+    /// Moves the cursor past `swap`, the swaps of one timestamp may come in any order.
+    pub fn advance(&mut self, swap: &Swap) -> Result<(), SubgraphError> {
+        let timestamp = swap.timestamp()?;
+        if timestamp != self.timestamp {
+            self.timestamp = timestamp;
+            self.processed.clear();
+        }
+        if !self.processed.contains(&swap.id) {
+            self.processed.push(swap.id.clone());
+        }
+        self.id = swap.id.clone();
+        Ok(())
+    }
+
+    // Swaps processed at the cursor timestamp, a cursor saved before the list was kept only knows its last swap.
+    pub(crate) fn processed_ids(&self) -> Vec<String> {
+        let mut ids = self.processed.clone();
+        if !self.id.is_empty() && !ids.contains(&self.id) {
+            ids.push(self.id.clone());
+        }
+        ids
+    }
+
+    // The query reads from the cursor timestamp on, the processed swaps of that timestamp are dropped wherever they are in the page.
+    pub(crate) fn newer(&self, mut swaps: Vec<Swap>) -> Vec<Swap> {
+        let processed = self.processed_ids();
+        swaps.retain(|swap| !processed.contains(&swap.id));
+        swaps
+    }
+}
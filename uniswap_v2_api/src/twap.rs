@@ -0,0 +1,32 @@
+use api_time::TimeRange;
+
+/// Blocks sampled per time weighted average price.
+pub const DEFAULT_TWAP_SAMPLES: u32 = 12;
+/// Period the time weighted average prices of [`UniswapV2Client::twap_perf`](crate::UniswapV2Client::twap_perf) are taken over.
+pub const DEFAULT_TWAP_WINDOW: TimeRange = TimeRange::Hours(1);
+
+/// Token price at a block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceSample {
+    pub block: i64,
+    pub price: f64,
+}
+
+/// Evenly spaced blocks from `from` to `to`, both included.
+pub fn sample_blocks(from: i64, to: i64, samples: u32) -> Vec<i64> {
+    if samples < 2 || from >= to {
+        return vec![to]
+    }
+    let steps = i64::from(samples - 1);
+    let mut blocks: Vec<i64> = (0..=steps).map(|step| from + (to - from) * step / steps).collect();
+    blocks.dedup();
+    blocks
+}
+
+/// Mean of the prices, evenly spaced blocks are close to evenly spaced in time.
+pub fn time_weighted_average(samples: &[PriceSample]) -> Option<f64> {
+    if samples.is_empty() {
+        return None
+    }
+    Some(samples.iter().map(|sample| sample.price).sum::<f64>() / samples.len() as f64)
+}
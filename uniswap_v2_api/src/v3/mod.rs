@@ -0,0 +1,255 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use api_policy::RequestPolicy;
+use api_time::{system_clock, Clock, TimeRange};
+use etherscan_io_api::{BlockEstimator, EtherscanClient};
+
+use crate::client::range_start_block;
+use crate::graphql::Subgraph;
+use crate::{BlockHeight, Query, Quote, SubgraphError, DEFAULT_LIQUIDITY_FLOOR, DEFAULT_QUOTE_TOKENS, DEFAULT_TIMEOUT};
+
+pub mod queries;
+use queries::{PoolsData, PoolsQuery};
+
+pub const UNISWAP_V3: &str = "https://api.thegraph.com/subgraphs/name/uniswap/uniswap-v3";
+
+/// Value locked in the pools of one fee tier.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeTierLiquidity {
+    /// Swap fee in hundredths of a basis point, eg.: 3000 for 0.3%.
+    pub fee_tier: u32,
+    pub pools: usize,
+    pub total_value_locked_usd: f64,
+    /// Volume traded since the pools were created.
+    pub volume_usd: f64,
+}
+
+impl FeeTierLiquidity {
+    /// Swap fee in percent, eg.: 0.3 for the 3000 tier.
+    pub fn fee_percent(&self) -> f64 {
+        f64::from(self.fee_tier) / 10_000.0
+    }
+}
+
+/// Uniswap V3 subgraph client pricing tokens from the pool square root prices.
+/// Cloning is cheap, clones share the same connection pool.
+#[derive(Debug, Clone)]
+pub struct UniswapV3Client {
+    subgraph: Subgraph,
+    blocks: BlockEstimator,
+    clock: Arc<dyn Clock>,
+    liquidity_floor: f64,
+    quote_tokens: Vec<String>,
+}
+
+impl UniswapV3Client {
+    /// Client of the hosted subgraph with the default timeout.
+    pub fn new() -> Result<Self> {
+        Self::builder().build()
+    }
+
+    pub fn builder() -> UniswapV3ClientBuilder {
+        UniswapV3ClientBuilder::default()
+    }
+
+    pub fn subgraph_url(&self) -> &str {
+        &self.subgraph.url
+    }
+
+    /// Estimator the blocks of past timestamps come from.
+    pub fn block_estimator(&self) -> &BlockEstimator {
+        &self.blocks
+    }
+
+    /// Clock the performance ranges end at.
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// Latest price of `token` in `quote`, averaged over the token pools weighted by their value locked.
+    pub async fn price(&self, token: &str, quote: &Quote) -> Result<f64> {
+        self.price_at(token, quote, None).await
+    }
+
+    /// Price of `token` in `quote` at the given block.
+    pub async fn price_at_block(&self, token: &str, quote: &Quote, block: i64) -> Result<f64> {
+        self.price_at(token, quote, Some(block)).await
+    }
+
+    /// Price change of `token` in `quote` over the range in percent,
+    /// the block at the start of the range comes from the block estimator.
+    pub async fn perf(&self, token: &str, quote: &Quote, range: TimeRange) -> Result<f64> {
+        let previous_block = range_start_block(&self.blocks, self.clock.now(), range).await?;
+        let price_now = self.price(token, quote).await?;
+        let price_previous = self.price_at_block(token, quote, previous_block).await?;
+        Ok((price_now / price_previous - 1.0) * 100.0)
+    }
+
+    /// Value locked in the `token` pools against the quote tokens per fee tier, lowest fee first.
+    pub async fn liquidity_by_fee_tier(&self, token: &str) -> Result<Vec<FeeTierLiquidity>> {
+        let data = self.pools(token, None).await?;
+        let mut fee_tiers: Vec<FeeTierLiquidity> = Vec::new();
+        for pool in data.pools() {
+            let fee_tier = pool.fee_tier()?;
+            let position = match fee_tiers.iter().position(|tier| tier.fee_tier == fee_tier) {
+                Some(position) => position,
+                None => {
+                    fee_tiers.push(FeeTierLiquidity { fee_tier, pools: 0, total_value_locked_usd: 0.0, volume_usd: 0.0 });
+                    fee_tiers.len() - 1
+                }
+            };
+            let tier = &mut fee_tiers[position];
+            tier.pools += 1;
+            tier.total_value_locked_usd += pool.total_value_locked_usd()?;
+            tier.volume_usd += pool.volume_usd()?;
+        }
+        fee_tiers.sort_by_key(|tier| tier.fee_tier);
+        Ok(fee_tiers)
+    }
+
+    /// Pools of `token` against the quote tokens at the given block, the latest if `None`.
+    pub async fn pools(&self, token: &str, block: Option<i64>) -> Result<PoolsData, SubgraphError> {
+        self.query(&PoolsQuery {
+            token: token.to_lowercase(),
+            quotes: self.quote_tokens.clone(),
+            block: BlockHeight::at(block),
+        }).await
+    }
+
+    /// Runs a typed query under the retry and rate limit policy,
+    /// a response with an `errors` array is an error even if it carries partial data.
+    pub async fn query<Q: Query>(&self, variables: &Q) -> Result<Q::Data, SubgraphError> {
+        self.subgraph.query(variables).await
+    }
+
+    async fn price_at(&self, token: &str, quote: &Quote, block: Option<i64>) -> Result<f64> {
+        let token_usd = self.usd_price_at(token, block).await?;
+        match quote {
+            Quote::Usd => Ok(token_usd),
+            Quote::Token(quote_token) => Ok(token_usd / self.usd_price_at(quote_token, block).await?),
+        }
+    }
+
+    async fn usd_price_at(&self, token: &str, block: Option<i64>) -> Result<f64> {
+        let data = self.pools(token, block).await?;
+        weighted_usd_price(&token.to_lowercase(), &data, self.liquidity_floor)
+    }
+}
+
+// Each pool prices the token in its other token, valued in USD through the subgraph ETH price.
+// Pools under the floor, without liquidity in range or against a token without a price don't count.
+fn weighted_usd_price(token: &str, data: &PoolsData, liquidity_floor: f64) -> Result<f64> {
+    let bundle = data.bundle.as_ref().ok_or(SubgraphError::MissingField { query: PoolsQuery::OPERATION, field: "bundle" })?;
+    let eth_price = bundle.eth_price_usd()?;
+
+    let mut weighted_sum = 0.0;
+    let mut liquidity = 0.0;
+    for pool in data.pools() {
+        let total_value_locked = pool.total_value_locked_usd()?;
+        let token0_price = pool.token0_price()?;
+        if total_value_locked < liquidity_floor || pool.liquidity()? == 0.0 || token0_price == 0.0 {
+            continue
+        }
+        let (other, price_in_other) = if pool.token0.id == token {
+            (&pool.token1, token0_price)
+        } else {
+            (&pool.token0, 1.0 / token0_price)
+        };
+        let other_usd = other.derived_eth()? * eth_price;
+        if other_usd == 0.0 {
+            continue
+        }
+        weighted_sum += price_in_other * other_usd * total_value_locked;
+        liquidity += total_value_locked;
+    }
+    if liquidity == 0.0 {
+        return Err(anyhow!("token {} has no priced pool with at least ${} locked", token, liquidity_floor))
+    }
+    Ok(weighted_sum / liquidity)
+}
+
+/// Builder for [`UniswapV3Client`], every option falls back to a sane default.
+#[derive(Debug, Clone)]
+pub struct UniswapV3ClientBuilder {
+    subgraph_url: String,
+    timeout: Duration,
+    policy: Option<Arc<RequestPolicy>>,
+    blocks: Option<BlockEstimator>,
+    clock: Option<Arc<dyn Clock>>,
+    liquidity_floor: f64,
+    quote_tokens: Vec<String>,
+}
+
+impl Default for UniswapV3ClientBuilder {
+    fn default() -> Self {
+        Self {
+            subgraph_url: UNISWAP_V3.into(),
+            timeout: DEFAULT_TIMEOUT,
+            policy: None,
+            blocks: None,
+            clock: None,
+            liquidity_floor: DEFAULT_LIQUIDITY_FLOOR,
+            quote_tokens: DEFAULT_QUOTE_TOKENS.iter().map(|token| token.to_string()).collect(),
+        }
+    }
+}
+
+impl UniswapV3ClientBuilder {
+    /// Subgraph url, eg.: a local mirror or mock server.
+    pub fn subgraph_url(mut self, subgraph_url: &str) -> Self {
+        self.subgraph_url = subgraph_url.into();
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Retry and rate limit policy, the process wide [`RequestPolicy::global`] if not set.
+    pub fn request_policy(mut self, policy: Arc<RequestPolicy>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Estimator the blocks of past timestamps come from, share one between clients to share its anchors.
+    /// An estimator of [`EtherscanClient::from_env`] without a cache file if not set.
+    pub fn block_estimator(mut self, blocks: BlockEstimator) -> Self {
+        self.blocks = Some(blocks);
+        self
+    }
+
+    /// Clock the performance ranges end at, the system clock if not set.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Minimum USD value locked in a pool counted in the price.
+    pub fn liquidity_floor(mut self, liquidity_floor: f64) -> Self {
+        self.liquidity_floor = liquidity_floor;
+        self
+    }
+
+    /// Token addresses the price looks up pools against.
+    pub fn quote_tokens(mut self, quote_tokens: &[&str]) -> Self {
+        self.quote_tokens = quote_tokens.iter().map(|token| token.to_lowercase()).collect();
+        self
+    }
+
+    pub fn build(self) -> Result<UniswapV3Client> {
+        let blocks = match self.blocks {
+            Some(blocks) => blocks,
+            None => BlockEstimator::new(EtherscanClient::from_env()?),
+        };
+        Ok(UniswapV3Client {
+            subgraph: Subgraph::new(self.subgraph_url, self.timeout, self.policy)?,
+            blocks,
+            clock: self.clock.unwrap_or_else(system_clock),
+            liquidity_floor: self.liquidity_floor,
+            quote_tokens: self.quote_tokens,
+        })
+    }
+}
@@ -0,0 +1,125 @@
+use std::borrow::Cow;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::graphql::{parse_number, BlockHeight, Query, SubgraphError};
+
+/// Pools of the token against any of the quote tokens, in both token orders, with the ETH price to value them.
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolsQuery {
+    pub token: String,
+    pub quotes: Vec<String>,
+    pub block: Option<BlockHeight>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PoolsData {
+    /// Pools with the token as token0.
+    #[serde(rename = "asToken0")]
+    pub as_token0: Vec<Pool>,
+    /// Pools with the token as token1.
+    #[serde(rename = "asToken1")]
+    pub as_token1: Vec<Pool>,
+    pub bundle: Option<Bundle>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Pool {
+    pub id: String,
+    /// Swap fee in hundredths of a basis point, eg.: 3000 for 0.3%.
+    #[serde(rename = "feeTier")]
+    pub fee_tier: String,
+    /// Liquidity in range at the current price.
+    pub liquidity: String,
+    /// Square root of the token0 price in token1 base units, Q64.96 fixed point.
+    #[serde(rename = "sqrtPrice")]
+    pub sqrt_price: String,
+    pub token0: PoolToken,
+    pub token1: PoolToken,
+    #[serde(rename = "totalValueLockedUSD")]
+    pub total_value_locked_usd: String,
+    #[serde(rename = "volumeUSD")]
+    pub volume_usd: String,
+    #[serde(rename = "txCount")]
+    pub tx_count: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PoolToken {
+    pub id: String,
+    pub symbol: String,
+    pub decimals: String,
+    #[serde(rename = "derivedETH")]
+    pub derived_eth: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Bundle {
+    #[serde(rename = "ethPriceUSD")]
+    pub eth_price_usd: String,
+}
+
+impl Query for PoolsQuery {
+    const OPERATION: &'static str = "Pools";
+    type Data = PoolsData;
+
+    fn document(&self) -> Cow<'static, str> {
+        Cow::Borrowed("query Pools($token: String!, $quotes: [String!]!, $block: Block_height) {\n  asToken0: pools(where: {token0: $token, token1_in: $quotes}, block: $block) {\n    ...PoolFields\n  }\n  asToken1: pools(where: {token0_in: $quotes, token1: $token}, block: $block) {\n    ...PoolFields\n  }\n  bundle(id: 1, block: $block) {\n    ethPriceUSD\n  }\n}\nfragment PoolFields on Pool {\n  id\n  feeTier\n  liquidity\n  sqrtPrice\n  token0 {\n    id\n    symbol\n    decimals\n    derivedETH\n  }\n  token1 {\n    id\n    symbol\n    decimals\n    derivedETH\n  }\n  totalValueLockedUSD\n  volumeUSD\n  txCount\n}")
+    }
+}
+
+impl PoolsData {
+    /// Pools in either token order.
+    pub fn pools(&self) -> impl Iterator<Item = &Pool> + Clone {
+        self.as_token0.iter().chain(&self.as_token1)
+    }
+}
+
+impl Pool {
+    pub fn fee_tier(&self) -> Result<u32, SubgraphError> {
+        parse_number(PoolsQuery::OPERATION, "pools.feeTier", &self.fee_tier)
+    }
+
+    pub fn liquidity(&self) -> Result<f64, SubgraphError> {
+        parse_number(PoolsQuery::OPERATION, "pools.liquidity", &self.liquidity)
+    }
+
+    pub fn sqrt_price(&self) -> Result<f64, SubgraphError> {
+        parse_number(PoolsQuery::OPERATION, "pools.sqrtPrice", &self.sqrt_price)
+    }
+
+    /// Token1 amount per token0 derived from the pool square root price, in whole tokens.
+    pub fn token0_price(&self) -> Result<f64, SubgraphError> {
+        let sqrt_price = self.sqrt_price()? / 2f64.powi(96);
+        let decimals = self.token0.decimals()? - self.token1.decimals()?;
+        Ok(sqrt_price * sqrt_price * 10f64.powi(decimals))
+    }
+
+    pub fn total_value_locked_usd(&self) -> Result<f64, SubgraphError> {
+        parse_number(PoolsQuery::OPERATION, "pools.totalValueLockedUSD", &self.total_value_locked_usd)
+    }
+
+    pub fn volume_usd(&self) -> Result<f64, SubgraphError> {
+        parse_number(PoolsQuery::OPERATION, "pools.volumeUSD", &self.volume_usd)
+    }
+
+    pub fn tx_count(&self) -> Result<u64, SubgraphError> {
+        parse_number(PoolsQuery::OPERATION, "pools.txCount", &self.tx_count)
+    }
+}
+
+impl PoolToken {
+    pub fn decimals(&self) -> Result<i32, SubgraphError> {
+        parse_number(PoolsQuery::OPERATION, "pools.token.decimals", &self.decimals)
+    }
+
+    pub fn derived_eth(&self) -> Result<f64, SubgraphError> {
+        parse_number(PoolsQuery::OPERATION, "pools.token.derivedETH", &self.derived_eth)
+    }
+}
+
+impl Bundle {
+    pub fn eth_price_usd(&self) -> Result<f64, SubgraphError> {
+        parse_number(PoolsQuery::OPERATION, "bundle.ethPriceUSD", &self.eth_price_usd)
+    }
+}
@@ -0,0 +1,81 @@
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+pub use test_support::no_retry_policy;
+use test_support::Fixtures;
+use api_time::FixedClock;
+use etherscan_io_api::{BlockEstimator, EtherscanClient};
+use serde_json::{json, Value};
+use uniswap_v2_api::{UniswapV2Client, UniswapV2ClientBuilder, UniswapV3Client, UniswapV3ClientBuilder};
+use wiremock::{Mock, MockServer};
+use wiremock::matchers::{body_partial_json, method, path, query_param};
+
+pub const SUBGRAPH_PATH: &str = "/subgraphs/name/uniswap/uniswap-v2";
+/// Time of the stand-in clients' clock, 2021-06-29 10:09:59 UTC.
+pub const NOW: u64 = 1_624_961_399;
+
+pub const FIXTURES: Fixtures = Fixtures::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures"));
+
+/// Local stand-in for the uniswap subgraph and etherscan with a client pointed at it, its clock stands at `NOW`.
+pub async fn stand_in() -> (MockServer, UniswapV2Client) {
+    test_support::stand_in(|server| {
+        let clock = Arc::new(FixedClock::new(NOW));
+        let etherscan = EtherscanClient::builder()
+            .base_url(&format!("{}/api", server.uri()))
+            .request_policy(no_retry_policy())
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+        UniswapV2Client::builder()
+            .subgraph_url(&format!("{}{}", server.uri(), SUBGRAPH_PATH))
+            .request_policy(no_retry_policy())
+            .block_estimator(BlockEstimator::new(etherscan))
+            .clock(clock)
+            .build()
+            .unwrap()
+    }).await
+}
+
+/// Client on the stand-in of `client` with the options of `options` set.
+pub fn client_with(client: &UniswapV2Client, options: impl FnOnce(UniswapV2ClientBuilder) -> UniswapV2ClientBuilder) -> UniswapV2Client {
+    let builder = UniswapV2Client::builder()
+        .subgraph_url(client.subgraph_url())
+        .block_estimator(client.block_estimator().clone())
+        .clock(client.clock().clone())
+        .request_policy(no_retry_policy());
+    options(builder).build().unwrap()
+}
+
+/// Uniswap V3 client on the stand-in of `client` with the options of `options` set.
+pub fn v3_client_with(client: &UniswapV2Client, options: impl FnOnce(UniswapV3ClientBuilder) -> UniswapV3ClientBuilder) -> UniswapV3Client {
+    let builder = UniswapV3Client::builder()
+        .subgraph_url(client.subgraph_url())
+        .block_estimator(client.block_estimator().clone())
+        .clock(client.clock().clone())
+        .request_policy(no_retry_policy());
+    options(builder).build().unwrap()
+}
+
+pub fn assert_close(value: f64, expected: f64) {
+    assert!((value - expected).abs() < 1e-9, "{} != {}", value, expected);
+}
+
+/// Answers the graphql operation when the request variables contain `variables`.
+pub async fn serve_query(server: &MockServer, operation: &str, variables: Value, status: u16, fixture_name: &str) {
+    Mock::given(method("POST"))
+        .and(path(SUBGRAPH_PATH))
+        .and(body_partial_json(json!({ "operationName": operation, "variables": variables })))
+        .respond_with(FIXTURES.json_response(status, fixture_name))
+        .mount(server)
+        .await;
+}
+
+pub async fn serve_block_number(server: &MockServer, fixture_name: &str) {
+    Mock::given(method("GET"))
+        .and(path("/api"))
+        .and(query_param("action", "getblocknobytime"))
+        .respond_with(FIXTURES.json_response(200, fixture_name))
+        .mount(server)
+        .await;
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use api_time::TimeRange;
+use common::{assert_close, serve_block_number, serve_query, stand_in};
+use serde_json::json;
+use uniswap_v2_api::ICAP;
+
+#[tokio::test]
+async fn liquidity_deltas() {
+    let (server, client) = stand_in().await;
+    serve_block_number(&server, "block.json").await;
+    serve_query(&server, "TokenPairs", json!({ "token": ICAP, "block": null }), 200, "token_pairs.json").await;
+    serve_query(&server, "TokenPairs", json!({ "token": ICAP, "block": { "number": 1000 } }), 200, "token_pairs_previous.json").await;
+
+    let stats = client.liquidity(ICAP, TimeRange::Days(1)).await.unwrap();
+    assert_eq!(stats.range, TimeRange::Days(1));
+    let names: Vec<_> = stats.pairs.iter().map(|pair| pair.name.as_str()).collect();
+    assert_eq!(names, vec!["DAI/ICAP", "ICAP/WETH", "ICAP/USDT"]);
+
+    let dai = &stats.pairs[0];
+    assert_close(dai.reserve_usd_delta, 10_000.0);
+    assert_close(dai.volume_usd_delta, 8_765.5);
+    assert_eq!(dai.tx_count_delta, 34);
+    // created within the range
+    let weth = &stats.pairs[1];
+    assert_close(weth.reserve_usd_delta, 50_000.0);
+    assert_eq!(weth.tx_count_delta, 321);
+    assert_close(stats.pairs[2].reserve_usd_delta, -300.0);
+
+    assert_close(stats.reserve_usd(), 150_500.0);
+    assert_close(stats.reserve_usd_delta(), 59_700.0);
+    assert_close(stats.volume_usd_delta(), 20_776.0);
+    assert_eq!(stats.tx_count_delta(), 357);
+}
+
+#[tokio::test]
+async fn liquidity_block_lookup_error() {
+    let (server, client) = stand_in().await;
+    serve_block_number(&server, "graphql_errors.json").await;
+
+    assert!(client.liquidity(ICAP, TimeRange::Days(1)).await.is_err());
+}
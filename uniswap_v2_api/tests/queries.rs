@@ -0,0 +1,109 @@
+mod common;
+
+use common::{serve_query, stand_in, SUBGRAPH_PATH};
+use serde_json::json;
+use uniswap_v2_api::queries::{PairsQuery, TokenDayDatasQuery, TokenQuery};
+use uniswap_v2_api::{BlockHeight, Query, SubgraphError, DAI, ICAP};
+use wiremock::{Mock, ResponseTemplate};
+use wiremock::matchers::{method, path};
+
+fn subgraph_error(error: anyhow::Error) -> SubgraphError {
+    error.downcast::<SubgraphError>().expect("expected a subgraph error")
+}
+
+#[tokio::test]
+async fn graphql_errors_name_the_query() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "TokenPrice", json!({}), 200, "graphql_errors.json").await;
+
+    match subgraph_error(client.derived_eth(ICAP, Some(99_999_999)).await.unwrap_err()) {
+        SubgraphError::Graphql { query, messages } => {
+            assert_eq!(query, "TokenPrice");
+            assert!(messages[0].contains("only indexed up to block number 990"));
+        },
+        other => panic!("expected graphql errors, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn errors_with_partial_data_fail() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "TokenPrice", json!({}), 200, "partial_data_errors.json").await;
+
+    let query = TokenQuery { id: ICAP.into(), block: None };
+    assert!(matches!(client.query(&query).await, Err(SubgraphError::Graphql { query: "TokenPrice", .. })));
+}
+
+#[tokio::test]
+async fn missing_entity_names_the_field() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "TokenPrice", json!({}), 200, "token_missing.json").await;
+
+    let error = subgraph_error(client.derived_eth(ICAP, None).await.unwrap_err());
+    assert!(matches!(error, SubgraphError::MissingField { query: "TokenPrice", field: "token" }));
+    assert_eq!(error.to_string(), "TokenPrice query returned no token");
+}
+
+#[tokio::test]
+async fn invalid_number_names_the_field() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "TokenPrice", json!({}), 200, "token_price_malformed.json").await;
+
+    let error = subgraph_error(client.derived_eth(ICAP, None).await.unwrap_err());
+    assert_eq!(error.to_string(), "TokenPrice query field token.derivedETH is not a number: n/a");
+}
+
+#[tokio::test]
+async fn unexpected_shape_is_a_decode_error() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "EthPrice", json!({}), 200, "bundle_renamed_field.json").await;
+
+    match subgraph_error(client.eth_price(None).await.unwrap_err()) {
+        SubgraphError::Decode { query, source } => {
+            assert_eq!(query, "EthPrice");
+            assert!(source.to_string().contains("ethPrice"));
+        },
+        other => panic!("expected a decode error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn http_error_without_graphql_response() {
+    let (server, client) = stand_in().await;
+    Mock::given(method("POST"))
+        .and(path(SUBGRAPH_PATH))
+        .respond_with(ResponseTemplate::new(502).set_body_string("Bad Gateway"))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(subgraph_error(client.eth_price(None).await.unwrap_err()), SubgraphError::HttpStatus(502)));
+}
+
+#[tokio::test]
+async fn pairs_query() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "Pairs", json!({ "token0": DAI, "token1": ICAP, "block": { "number": 990 } }), 200, "pairs.json").await;
+
+    let query = PairsQuery { token0: DAI.into(), token1: ICAP.into(), block: BlockHeight::at(Some(990)) };
+    let pairs = client.query(&query).await.unwrap().pairs;
+    assert_eq!(pairs.len(), 1);
+    assert_eq!(pairs[0].token1.symbol, "ICAP");
+    assert_eq!(pairs[0].token0_price(PairsQuery::OPERATION).unwrap(), 4.12345678);
+    assert_eq!(pairs[0].reserve_usd(PairsQuery::OPERATION).unwrap(), 152340.25);
+    assert_eq!(pairs[0].tx_count(PairsQuery::OPERATION).unwrap(), 1234);
+}
+
+#[tokio::test]
+async fn token_day_datas_query() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "TokenDayDatas", json!({ "token": ICAP, "days": 2 }), 200, "token_day_datas.json").await;
+
+    let days = client.query(&TokenDayDatasQuery { token: ICAP.into(), days: 2 }).await.unwrap().token_day_datas;
+    assert_eq!(days[0].date, 1_615_766_400);
+    assert_eq!(days[0].price_usd().unwrap(), 4.2);
+    assert_eq!(days[1].total_liquidity_usd().unwrap(), 148000.0);
+    assert!(matches!(
+        days[1].daily_volume_usd(),
+        Err(SubgraphError::InvalidNumber { query: "TokenDayDatas", field: "tokenDayDatas.dailyVolumeUSD", .. })
+    ));
+}
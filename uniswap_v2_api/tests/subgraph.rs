@@ -0,0 +1,173 @@
+mod common;
+
+use api_time::{TimeRange, DEFAULT_RANGES};
+use common::{serve_block_number, serve_query, stand_in, FIXTURES, NOW, SUBGRAPH_PATH};
+use serde_json::json;
+use uniswap_v2_api::{Quote, DAI, ICAP};
+use wiremock::Mock;
+use wiremock::matchers::{body_partial_json, method, path, query_param};
+
+fn assert_close(value: f64, expected: f64) {
+    assert!((value - expected).abs() < 1e-9, "{} != {}", value, expected);
+}
+
+#[tokio::test]
+async fn price_in_usd() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "TokenPrice", json!({ "id": ICAP, "block": null }), 200, "token_price_now.json").await;
+    serve_query(&server, "EthPrice", json!({ "block": null }), 200, "eth_price.json").await;
+
+    assert_close(client.price(ICAP, &Quote::Usd).await.unwrap(), 4.001);
+}
+
+#[tokio::test]
+async fn price_in_quote_token() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "TokenPrice", json!({ "id": ICAP }), 200, "token_price_now.json").await;
+    serve_query(&server, "TokenPrice", json!({ "id": DAI }), 200, "token_price_dai.json").await;
+
+    let quote = Quote::Token(DAI.to_uppercase());
+    assert_close(client.price(&ICAP.to_uppercase(), &quote).await.unwrap(), 4.0);
+}
+
+#[tokio::test]
+async fn price_at_block_success() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "TokenPrice", json!({ "block": { "number": 990 } }), 200, "token_price_now.json").await;
+    serve_query(&server, "EthPrice", json!({ "block": { "number": 990 } }), 200, "eth_price.json").await;
+
+    assert_close(client.price_at_block(ICAP, &Quote::Usd, 990).await.unwrap(), 4.001);
+}
+
+#[tokio::test]
+async fn price_malformed() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "TokenPrice", json!({}), 200, "token_price_malformed.json").await;
+    serve_query(&server, "EthPrice", json!({}), 200, "eth_price.json").await;
+
+    assert!(client.price(ICAP, &Quote::Usd).await.is_err());
+}
+
+#[tokio::test]
+async fn price_unknown_token() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "TokenPrice", json!({}), 200, "token_missing.json").await;
+
+    assert!(client.derived_eth(ICAP, None).await.is_err());
+}
+
+#[tokio::test]
+async fn price_graphql_errors() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "TokenPrice", json!({}), 200, "graphql_errors.json").await;
+
+    assert!(client.price_at_block(ICAP, &Quote::Usd, 99_999_999).await.is_err());
+}
+
+#[tokio::test]
+async fn quote_token_without_price() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "TokenPrice", json!({ "id": ICAP }), 200, "token_price_now.json").await;
+    serve_query(&server, "TokenPrice", json!({ "id": DAI }), 200, "token_price_zero.json").await;
+
+    assert!(client.price(ICAP, &Quote::Token(DAI.into())).await.is_err());
+}
+
+#[tokio::test]
+async fn eth_price_success() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "EthPrice", json!({ "block": { "number": 990 } }), 200, "eth_price.json").await;
+
+    assert_close(client.eth_price(Some(990)).await.unwrap(), 2000.5);
+}
+
+#[tokio::test]
+async fn eth_price_error() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "EthPrice", json!({}), 500, "graphql_errors.json").await;
+
+    assert!(client.eth_price(Some(990)).await.is_err());
+}
+
+async fn serve_perf(server: &wiremock::MockServer) {
+    serve_block_number(server, "block.json").await;
+    serve_query(server, "PriceSamples", json!({ "token": ICAP }), 200, "perf_samples.json").await;
+}
+
+#[tokio::test]
+async fn perf_success() {
+    let (server, client) = stand_in().await;
+    serve_perf(&server).await;
+
+    assert_close(client.perf(ICAP, &Quote::Usd, TimeRange::Days(1)).await.unwrap(), 100.0);
+}
+
+#[tokio::test]
+async fn perf_year_range() {
+    let (server, client) = stand_in().await;
+    serve_perf(&server).await;
+
+    assert_close(client.perf(ICAP, &Quote::Usd, TimeRange::Years(1)).await.unwrap(), 100.0);
+}
+
+#[tokio::test]
+async fn repeated_perf_needs_one_block_lookup() {
+    let (server, client) = stand_in().await;
+    Mock::given(method("GET"))
+        .and(path("/api"))
+        .and(query_param("action", "getblocknobytime"))
+        .and(query_param("timestamp", (NOW - 86_400).to_string().as_str()))
+        .respond_with(FIXTURES.json_response(200, "block.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    serve_query(&server, "PriceSamples", json!({ "token": ICAP }), 200, "perf_samples.json").await;
+
+    for _ in 0..3 {
+        assert_close(client.perf(ICAP, &Quote::Usd, TimeRange::Days(1)).await.unwrap(), 100.0);
+    }
+}
+
+#[tokio::test]
+async fn perf_without_previous_price() {
+    let (server, client) = stand_in().await;
+    serve_block_number(&server, "block.json").await;
+    serve_query(&server, "PriceSamples", json!({}), 200, "perf_samples_missing.json").await;
+
+    assert!(client.perf(ICAP, &Quote::Usd, TimeRange::Days(1)).await.is_err());
+}
+
+#[tokio::test]
+async fn perf_ranges_in_one_request() {
+    let (server, client) = stand_in().await;
+    serve_block_number(&server, "block.json").await;
+    Mock::given(method("POST"))
+        .and(path(SUBGRAPH_PATH))
+        .and(body_partial_json(json!({ "operationName": "PriceSamples" })))
+        .respond_with(FIXTURES.json_response(200, "perf_ranges.json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let perf = client.perf_ranges(ICAP, &Quote::Usd, &DEFAULT_RANGES).await.unwrap();
+    let expected = [0.0, -20.0, 100.0, -50.0, 100.0, 100.0];
+    assert_eq!(perf.len(), expected.len());
+    for (perf, expected) in perf.into_iter().zip(expected) {
+        assert_close(perf, expected);
+    }
+}
+
+#[tokio::test]
+async fn perf_block_lookup_error() {
+    let (server, client) = stand_in().await;
+    serve_block_number(&server, "graphql_errors.json").await;
+
+    assert!(client.perf(ICAP, &Quote::Usd, TimeRange::Days(1)).await.is_err());
+}
+
+#[test]
+fn quote_from_config() {
+    assert_eq!(" USD ".parse::<Quote>().unwrap(), Quote::Usd);
+    assert_eq!(DAI.to_uppercase().parse::<Quote>().unwrap(), Quote::Token(DAI.into()));
+    assert_eq!(Quote::Token(DAI.into()).to_string(), DAI);
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use common::{serve_query, stand_in};
+use serde_json::json;
+use uniswap_v2_api::{SwapCursor, ICAP, SWAPS_PAGE};
+
+const DAI_ICAP: &str = "0x1bc2fd6d1d5dc1b0a8fa9d8e36b7e4a5e1be6e9f";
+
+#[tokio::test]
+async fn swaps_from_timestamp() {
+    let (server, client) = stand_in().await;
+    let variables = json!({ "pairs": [DAI_ICAP], "since": 1620000000, "first": SWAPS_PAGE });
+    serve_query(&server, "Swaps", variables, 200, "swaps.json").await;
+
+    let swaps = client.swaps_after(&[DAI_ICAP.to_uppercase()], &SwapCursor::starting_at(1620000000)).await.unwrap();
+    assert_eq!(swaps.len(), 3);
+    assert_eq!(swaps[1].amount_usd().unwrap(), 99800.0);
+    assert_eq!(swaps[1].token_amount(ICAP).unwrap(), -25000.0);
+    assert_eq!(swaps[2].token_amount(ICAP).unwrap(), 14250.0);
+    assert_eq!(swaps[2].transaction(), "0x5c8e2a4f6b1d3e7c9a0f2b4d6e8c1a3f5b7d9e0c2a4f6b8d1e3c5a7f9b0d2e4c");
+    assert_eq!(SwapCursor::of(&swaps[2]).unwrap().timestamp, 1620000180);
+}
+
+#[tokio::test]
+async fn swaps_after_cursor_skip_processed() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "Swaps", json!({ "since": 1620000000 }), 200, "swaps.json").await;
+
+    // the first swap of the block was processed before a restart
+    let cursor = SwapCursor {
+        timestamp: 1620000000,
+        id: "0x9f1c55a3e0b2bd0f6a3c8f0e4f5d1b7a6c2e3d4f5a6b7c8d9e0f1a2b3c4d5e6f-2".into(),
+        processed: Vec::new(),
+    };
+    let swaps = client.swaps_after(&[DAI_ICAP.into()], &cursor).await.unwrap();
+    let ids: Vec<_> = swaps.iter().map(|swap| &swap.id[..6]).collect();
+    assert_eq!(ids, vec!["0x0a7e", "0x5c8e"]);
+}
+
+#[tokio::test]
+async fn swaps_of_the_cursor_timestamp_in_any_order() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "Swaps", json!({ "since": 1620000000 }), 200, "swaps.json").await;
+
+    // both swaps of the first block were processed, the page lists them in another order than before
+    let mut cursor = SwapCursor::starting_at(1620000000);
+    let swaps = client.swaps_after(&[DAI_ICAP.into()], &cursor).await.unwrap();
+    cursor.advance(&swaps[1]).unwrap();
+    cursor.advance(&swaps[0]).unwrap();
+    assert_eq!(cursor.id, swaps[0].id);
+    assert_eq!(cursor.processed.len(), 2);
+
+    let swaps = client.swaps_after(&[DAI_ICAP.into()], &cursor).await.unwrap();
+    let ids: Vec<_> = swaps.iter().map(|swap| &swap.id[..6]).collect();
+    assert_eq!(ids, vec!["0x5c8e"]);
+
+    cursor.advance(&swaps[0]).unwrap();
+    assert_eq!(cursor, SwapCursor::of(&swaps[0]).unwrap());
+}
+
+#[tokio::test]
+async fn processed_swaps_are_left_out_of_the_query() {
+    let (server, client) = stand_in().await;
+    let processed = "0x9f1c55a3e0b2bd0f6a3c8f0e4f5d1b7a6c2e3d4f5a6b7c8d9e0f1a2b3c4d5e6f-2";
+    serve_query(&server, "Swaps", json!({ "since": 1620000000, "processed": [processed] }), 200, "swaps.json").await;
+
+    let cursor = SwapCursor { timestamp: 1620000000, id: processed.into(), processed: Vec::new() };
+    assert_eq!(client.swaps_after(&[DAI_ICAP.into()], &cursor).await.unwrap().len(), 2);
+}
+
+#[test]
+fn cursor_saved_without_the_processed_list_loads() {
+    let cursor: SwapCursor = serde_json::from_value(json!({ "timestamp": 1620000000, "id": "0xabc-1" })).unwrap();
+    assert_eq!(cursor, SwapCursor { timestamp: 1620000000, id: "0xabc-1".into(), processed: Vec::new() });
+}
+
+#[tokio::test]
+async fn swaps_error() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "Swaps", json!({}), 200, "graphql_errors.json").await;
+
+    assert!(client.swaps_after(&[DAI_ICAP.into()], &SwapCursor::default()).await.is_err());
+}
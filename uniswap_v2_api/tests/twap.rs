@@ -0,0 +1,77 @@
+mod common;
+
+use api_time::TimeRange;
+use common::{assert_close, client_with, serve_block_number, serve_query, stand_in};
+use serde_json::json;
+use uniswap_v2_api::queries::PriceSamplesQuery;
+use uniswap_v2_api::{sample_blocks, PriceSample, Query, Quote, DAI, ICAP};
+
+#[test]
+fn evenly_spaced_blocks() {
+    assert_eq!(sample_blocks(1000, 1100, 3), vec![1000, 1050, 1100]);
+    assert_eq!(sample_blocks(1000, 1002, 5), vec![1000, 1001, 1002]);
+    assert_eq!(sample_blocks(1000, 1000, 3), vec![1000]);
+    assert_eq!(sample_blocks(900, 1000, 1), vec![1000]);
+}
+
+#[test]
+fn samples_are_aliased_per_block() {
+    let query = PriceSamplesQuery { token: ICAP.into(), quote: None, blocks: vec![Some(1000), None] };
+    let document = query.document();
+    assert!(document.starts_with("query PriceSamples($token: String!) {"));
+    assert!(document.contains("token0: token(id: $token, block: {number: 1000})"));
+    assert!(document.contains("eth0: bundle(id: 1, block: {number: 1000})"));
+    // the latest indexed block
+    assert!(document.contains("token1: token(id: $token) {"));
+    assert!(document.contains("eth1: bundle(id: 1) {"));
+    assert!(!document.contains("quote"));
+
+    let query = PriceSamplesQuery { token: ICAP.into(), quote: Some(DAI.into()), blocks: vec![Some(1000)] };
+    let document = query.document();
+    assert!(document.starts_with("query PriceSamples($token: String!, $quote: String!) {"));
+    assert!(document.contains("quote0: token(id: $quote, block: {number: 1000})"));
+    assert!(!document.contains("bundle"));
+}
+
+#[tokio::test]
+async fn twap_over_range() {
+    let (server, client) = stand_in().await;
+    serve_block_number(&server, "block.json").await;
+    serve_query(&server, "IndexedBlock", json!({}), 200, "indexed_block.json").await;
+    serve_query(&server, "PriceSamples", json!({ "token": ICAP }), 200, "price_samples.json").await;
+
+    // 2$, 2$ and 4$ at blocks 1000, 1050 and 1100
+    let twap = client_with(&client, |builder| builder.twap_samples(3)).twap(ICAP, &Quote::Usd, TimeRange::Days(1)).await.unwrap();
+    assert_close(twap, 8.0 / 3.0);
+}
+
+#[tokio::test]
+async fn twap_perf_compares_windows() {
+    let (server, client) = stand_in().await;
+    serve_block_number(&server, "block.json").await;
+    serve_query(&server, "IndexedBlock", json!({}), 200, "indexed_block.json").await;
+    serve_query(&server, "PriceSamples", json!({}), 200, "price_samples.json").await;
+
+    // 2$ at the range start, then 2$, 4$ and 5$ at blocks 1000, 1050 and 1100 of the window before now
+    let perf = client_with(&client, |builder| builder.twap_samples(3)).twap_perf(ICAP, &Quote::Usd, TimeRange::Weeks(1)).await.unwrap();
+    assert_close(perf, (11.0 / 3.0 / 2.0 - 1.0) * 100.0);
+}
+
+#[tokio::test]
+async fn samples_without_token_are_left_out() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "PriceSamples", json!({ "quote": DAI }), 200, "price_samples_quote.json").await;
+
+    let samples = client.price_samples(ICAP, &Quote::Token(DAI.into()), &[900, 1000]).await.unwrap();
+    assert_eq!(samples, vec![PriceSample { block: 1000, price: 4.0 }]);
+}
+
+#[tokio::test]
+async fn twap_without_prices() {
+    let (server, client) = stand_in().await;
+    serve_block_number(&server, "block.json").await;
+    serve_query(&server, "IndexedBlock", json!({}), 200, "indexed_block.json").await;
+    serve_query(&server, "PriceSamples", json!({}), 200, "price_samples_quote.json").await;
+
+    assert!(client.twap(ICAP, &Quote::Usd, TimeRange::Days(1)).await.is_err());
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{assert_close, serve_query, stand_in, v3_client_with};
+use serde_json::json;
+use uniswap_v2_api::v3::FeeTierLiquidity;
+use uniswap_v2_api::{Dex, DexVersion, Quote, ICAP, USDC};
+
+#[tokio::test]
+async fn price_from_sqrt_prices() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "Pools", json!({ "token": ICAP, "block": null }), 200, "v3_pools.json").await;
+
+    // USDC pool at $4 with $300k, USDT pool at $4.5 and WETH pool at $5 with $100k each, the $1k DAI pool is under the floor
+    let price = v3_client_with(&client, |builder| builder.liquidity_floor(10_000.0)).price(&ICAP.to_uppercase(), &Quote::Usd).await.unwrap();
+    assert_close(price, 4.3);
+}
+
+#[tokio::test]
+async fn price_at_block_without_floor() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "Pools", json!({ "block": { "number": 990 } }), 200, "v3_pools.json").await;
+
+    let price = v3_client_with(&client, |builder| builder.liquidity_floor(0.0)).price_at_block(ICAP, &Quote::Usd, 990).await.unwrap();
+    assert_close(price, (2_150_000.0 + 9_000.0) / 501_000.0);
+}
+
+#[tokio::test]
+async fn liquidity_per_fee_tier() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "Pools", json!({}), 200, "v3_pools.json").await;
+
+    let fee_tiers = v3_client_with(&client, |builder| builder.liquidity_floor(10_000.0)).liquidity_by_fee_tier(ICAP).await.unwrap();
+    assert_eq!(fee_tiers, vec![
+        FeeTierLiquidity { fee_tier: 500, pools: 1, total_value_locked_usd: 300_000.0, volume_usd: 1_200_000.0 },
+        FeeTierLiquidity { fee_tier: 3000, pools: 2, total_value_locked_usd: 200_000.0, volume_usd: 890_000.0 },
+        FeeTierLiquidity { fee_tier: 10000, pools: 1, total_value_locked_usd: 1_000.0, volume_usd: 2_500.0 },
+    ]);
+    assert_close(fee_tiers[1].fee_percent(), 0.3);
+}
+
+#[tokio::test]
+async fn dex_versions_behind_one_trait() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "Pools", json!({}), 200, "v3_pools.json").await;
+    serve_query(&server, "TokenPairs", json!({}), 200, "token_pairs.json").await;
+
+    let dexes: Vec<Arc<dyn Dex>> = vec![Arc::new(client.clone()), Arc::new(v3_client_with(&client, |builder| builder.liquidity_floor(10_000.0)))];
+    assert_eq!(dexes[0].version(), DexVersion::V2);
+    assert_eq!(dexes[1].version(), DexVersion::V3);
+    assert_close(dexes[0].liquidity_usd(ICAP).await.unwrap(), 150_500.0);
+    assert_close(dexes[1].liquidity_usd(ICAP).await.unwrap(), 501_000.0);
+    assert_close(dexes[1].usd_price(ICAP).await.unwrap(), 4.3);
+}
+
+#[tokio::test]
+async fn quote_token_without_pools() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "Pools", json!({ "token": ICAP }), 200, "v3_pools.json").await;
+    serve_query(&server, "Pools", json!({ "token": USDC }), 200, "graphql_errors.json").await;
+
+    assert!(v3_client_with(&client, |builder| builder.liquidity_floor(10_000.0)).price(ICAP, &Quote::Token(USDC.into())).await.is_err());
+}
+
+#[test]
+fn dex_version_from_config() {
+    assert_eq!(serde_json::from_str::<DexVersion>("\"v3\"").unwrap(), DexVersion::V3);
+    assert!(serde_json::from_str::<DexVersion>("\"v4\"").is_err());
+    assert_eq!(DexVersion::default(), DexVersion::V2);
+}
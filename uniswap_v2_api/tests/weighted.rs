@@ -0,0 +1,60 @@
+mod common;
+
+use common::{assert_close, client_with, serve_query, stand_in};
+use serde_json::json;
+use uniswap_v2_api::{SubgraphError, DAI, ICAP, USDC, USDT, WETH};
+use wiremock::{Mock, ResponseTemplate};
+use wiremock::matchers::method;
+
+#[tokio::test]
+async fn weighted_price_skips_thin_pairs() {
+    let (server, client) = stand_in().await;
+    let variables = json!({ "token": ICAP, "quotes": [DAI, USDC, USDT, WETH], "block": null });
+    serve_query(&server, "TokenPairs", variables, 200, "token_pairs.json").await;
+
+    // DAI pair at $4.0 with $100k and WETH pair at $4.2 with $50k, the $500 USDT pair is under the floor
+    assert_close(client.weighted_price(&ICAP.to_uppercase()).await.unwrap(), 610_000.0 / 150_000.0);
+}
+
+#[tokio::test]
+async fn weighted_price_without_floor() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "TokenPairs", json!({ "block": { "number": 990 } }), 200, "token_pairs.json").await;
+
+    let price = client_with(&client, |builder| builder.liquidity_floor(0.0)).weighted_price_at_block(ICAP, 990).await.unwrap();
+    assert_close(price, 614_500.0 / 150_500.0);
+}
+
+#[tokio::test]
+async fn weighted_price_quote_tokens() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "TokenPairs", json!({ "quotes": [DAI] }), 200, "token_pairs.json").await;
+
+    let client = client_with(&client, |builder| builder.quote_tokens(&[&DAI.to_uppercase()]));
+    assert!(client.weighted_price(ICAP).await.is_ok());
+}
+
+#[tokio::test]
+async fn weighted_price_without_liquid_pairs() {
+    let (server, client) = stand_in().await;
+    serve_query(&server, "TokenPairs", json!({}), 200, "token_pairs.json").await;
+
+    let error = client_with(&client, |builder| builder.liquidity_floor(1_000_000.0)).weighted_price(ICAP).await.unwrap_err();
+    assert!(error.to_string().contains("no priced pair"), "{}", error);
+}
+
+#[tokio::test]
+async fn malformed_pair_names_the_token_pairs_query() {
+    let (server, client) = stand_in().await;
+    let body = common::FIXTURES.read("token_pairs.json").replacen("\"reserveUSD\": \"50000\"", "\"reserveUSD\": \"n/a\"", 1);
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "application/json"))
+        .mount(&server)
+        .await;
+
+    let error = client.weighted_price(ICAP).await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<SubgraphError>(),
+        Some(SubgraphError::InvalidNumber { query: "TokenPairs", field: "pairs.reserveUSD", .. })
+    ), "{}", error);
+}